@@ -0,0 +1,156 @@
+use log::warn;
+use rand::Rng;
+use reqwest::Response;
+use std::future::Future;
+use std::time::Duration;
+use tokio::time::sleep;
+
+use crate::config::AiCoreConfig;
+use crate::error::GenesisError;
+
+const BASE_BACKOFF: Duration = Duration::from_millis(200);
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+/// 429 waits don't consume the ordinary retry budget (a well-behaved server telling us
+/// to slow down isn't a failure), but they still need a ceiling — otherwise a stuck or
+/// misbehaving AI Core spins this loop forever while holding the caller's generation
+/// permit. Cap both how many 429s we'll wait out and how long we'll spend waiting.
+const MAX_RATE_LIMIT_WAITS: u32 = 10;
+const MAX_RATE_LIMIT_WAIT_TOTAL: Duration = Duration::from_secs(120);
+
+/// Retries an idempotent AI Core call with full-jitter exponential backoff, honoring
+/// `AiCoreConfig::max_retries`. Only connection errors, timeouts, and 502/503/504 are
+/// retried; a 429 waits exactly as long as `Retry-After` says and doesn't consume the
+/// retry budget, up to `MAX_RATE_LIMIT_WAITS`/`MAX_RATE_LIMIT_WAIT_TOTAL`. `make_request`
+/// is called again from scratch on every attempt.
+pub async fn request_with_retry<F, Fut>(config: &AiCoreConfig, mut make_request: F) -> Result<Response, GenesisError>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<Response, reqwest::Error>>,
+{
+    let mut attempt = 0;
+    let mut rate_limit_waits = 0;
+    let mut rate_limit_wait_total = Duration::ZERO;
+
+    loop {
+        match make_request().await {
+            Ok(response) if response.status().as_u16() == 429 => {
+                let delay = retry_after_delay(&response).unwrap_or(Duration::from_secs(1));
+
+                if rate_limit_waits >= MAX_RATE_LIMIT_WAITS
+                    || rate_limit_wait_total + delay > MAX_RATE_LIMIT_WAIT_TOTAL
+                {
+                    return Err(GenesisError::AiCoreError(format!(
+                        "AI Core kept rate limiting us after {} waits totaling {:?}, giving up",
+                        rate_limit_waits, rate_limit_wait_total
+                    )));
+                }
+
+                rate_limit_waits += 1;
+                rate_limit_wait_total += delay;
+                warn!("AI Core rate limited us, waiting {:?} per Retry-After", delay);
+                sleep(delay).await;
+            }
+            Ok(response) if is_retryable_status(response.status().as_u16()) => {
+                if attempt >= config.max_retries {
+                    return Err(GenesisError::AiCoreError(format!(
+                        "AI Core returned {} after {} retries",
+                        response.status(),
+                        attempt
+                    )));
+                }
+                attempt += 1;
+                let delay = full_jitter_backoff(attempt);
+                warn!(
+                    "AI Core returned {}, retrying in {:?} (attempt {}/{})",
+                    response.status(), delay, attempt, config.max_retries
+                );
+                sleep(delay).await;
+            }
+            Ok(response) => return Ok(response),
+            Err(e) if is_retryable_error(&e) && attempt < config.max_retries => {
+                attempt += 1;
+                let delay = full_jitter_backoff(attempt);
+                warn!(
+                    "AI Core request failed ({}), retrying in {:?} (attempt {}/{})",
+                    e, delay, attempt, config.max_retries
+                );
+                sleep(delay).await;
+            }
+            Err(e) => return Err(e.into()),
+        }
+    }
+}
+
+fn is_retryable_status(status: u16) -> bool {
+    matches!(status, 502..=504)
+}
+
+fn is_retryable_error(err: &reqwest::Error) -> bool {
+    err.is_connect() || err.is_timeout()
+}
+
+fn full_jitter_backoff(attempt: u32) -> Duration {
+    let cap = MAX_BACKOFF.as_millis() as u64;
+    let base = BASE_BACKOFF.as_millis() as u64;
+    let delay = base.saturating_mul(1u64 << attempt.min(16)).min(cap);
+    let jittered = rand::thread_rng().gen_range(0..=delay.max(1));
+    Duration::from_millis(jittered)
+}
+
+fn retry_after_delay(response: &Response) -> Option<Duration> {
+    let value = response.headers().get("Retry-After")?.to_str().ok()?;
+    parse_retry_after(value)
+}
+
+/// Parses a `Retry-After` header value, which per RFC 9110 is either a delta-seconds
+/// integer or an HTTP-date. Split out from `retry_after_delay` so it's testable without
+/// constructing a real `reqwest::Response`.
+fn parse_retry_after(value: &str) -> Option<Duration> {
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+
+    let when = chrono::DateTime::parse_from_rfc2822(value).ok()?.with_timezone(&chrono::Utc);
+    let remaining = (when - chrono::Utc::now()).num_seconds().max(0) as u64;
+    Some(Duration::from_secs(remaining))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn full_jitter_backoff_stays_within_exponential_cap() {
+        for attempt in 0..20 {
+            let cap = BASE_BACKOFF.as_millis() as u64 * (1u64 << attempt.min(16));
+            let cap = cap.min(MAX_BACKOFF.as_millis() as u64);
+            let delay = full_jitter_backoff(attempt).as_millis() as u64;
+            assert!(delay <= cap, "attempt {} produced {}ms, expected <= {}ms", attempt, delay, cap);
+        }
+    }
+
+    #[test]
+    fn full_jitter_backoff_never_exceeds_max_backoff() {
+        let delay = full_jitter_backoff(63);
+        assert!(delay <= MAX_BACKOFF);
+    }
+
+    #[test]
+    fn parse_retry_after_accepts_delta_seconds() {
+        assert_eq!(parse_retry_after("30"), Some(Duration::from_secs(30)));
+    }
+
+    #[test]
+    fn parse_retry_after_accepts_http_date() {
+        let future = chrono::Utc::now() + chrono::Duration::seconds(10);
+        let header = future.to_rfc2822();
+        let delay = parse_retry_after(&header).expect("should parse RFC2822 date");
+        assert!(delay <= Duration::from_secs(10) && delay > Duration::from_secs(0));
+    }
+
+    #[test]
+    fn parse_retry_after_rejects_garbage() {
+        assert_eq!(parse_retry_after("not-a-valid-value"), None);
+    }
+}
@@ -0,0 +1,130 @@
+use actix_web::body::EitherBody;
+use actix_web::dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::{HttpMessage, ResponseError};
+use arc_swap::ArcSwap;
+use futures_util::future::{ready, LocalBoxFuture, Ready};
+use std::sync::Arc;
+
+use crate::config::Config;
+use crate::error::GenesisError;
+
+/// The identity a request authenticated as, stashed in `req.extensions()` by
+/// `ApiKeyAuthMiddleware` so downstream handlers and `validation::get_client_id` /
+/// `validation::RateLimit` can key on it instead of the caller's IP.
+#[derive(Debug, Clone)]
+pub struct AuthenticatedClient {
+    pub client_id: String,
+    pub max_requests: Option<usize>,
+    pub window_seconds: Option<u64>,
+}
+
+/// Actix middleware gating a scope behind `Config.api_keys`. Accepts the key via
+/// `Authorization: Bearer <key>` or `X-API-Key`, rejects unknown keys with a 401, and
+/// inserts an `AuthenticatedClient` extension on success. When `api_keys.keys` is empty
+/// the scope is left open, so existing deployments without a key list keep working
+/// unauthenticated.
+pub struct ApiKeyAuth {
+    config: Arc<ArcSwap<Config>>,
+}
+
+impl ApiKeyAuth {
+    pub fn new(config: Arc<ArcSwap<Config>>) -> Self {
+        Self { config }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for ApiKeyAuth
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = actix_web::Error;
+    type Transform = ApiKeyAuthMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(ApiKeyAuthMiddleware { service, config: self.config.clone() }))
+    }
+}
+
+pub struct ApiKeyAuthMiddleware<S> {
+    service: S,
+    config: Arc<ArcSwap<Config>>,
+}
+
+impl<S, B> Service<ServiceRequest> for ApiKeyAuthMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = actix_web::Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let config = self.config.load_full();
+
+        if config.api_keys.keys.is_empty() {
+            let fut = self.service.call(req);
+            return Box::pin(async move { Ok(fut.await?.map_into_left_body()) });
+        }
+
+        let presented_key = extract_key(&req);
+
+        let authenticated = presented_key
+            .as_deref()
+            .and_then(|key| config.api_keys.find(key))
+            .map(|entry| AuthenticatedClient {
+                client_id: entry.client_id.clone(),
+                max_requests: entry.max_requests,
+                window_seconds: entry.window_seconds,
+            });
+
+        match authenticated {
+            Some(authenticated) => {
+                let client_id = authenticated.client_id.clone();
+                req.extensions_mut().insert(authenticated);
+                let fut = self.service.call(req);
+                Box::pin(async move {
+                    // Overwrite the `http_request` span's `client_id` field (set from the
+                    // caller's IP by `telemetry::RequestTelemetry`, which runs before this
+                    // middleware) now that we know the authenticated key's client_id.
+                    tracing::Span::current().record("client_id", tracing::field::display(&client_id));
+                    Ok(fut.await?.map_into_left_body())
+                })
+            }
+            None => {
+                let (req, _) = req.into_parts();
+                Box::pin(async move {
+                    // Built inside the returned future, which runs within the request-id
+                    // scope `telemetry::RequestTelemetry` sets up, so `ApiError::request_id`
+                    // is filled in.
+                    let error = GenesisError::Unauthorized("Missing or invalid API key".to_string());
+                    let response = error.error_response();
+                    Ok(ServiceResponse::new(req, response.map_into_right_body()))
+                })
+            }
+        }
+    }
+}
+
+/// Reads `Authorization: Bearer <key>` first, falling back to `X-API-Key` for clients
+/// that can't set an `Authorization` header (e.g. some browser-based integrations).
+fn extract_key(req: &ServiceRequest) -> Option<String> {
+    if let Some(header) = req.headers().get("Authorization").and_then(|h| h.to_str().ok()) {
+        if let Some(key) = header.strip_prefix("Bearer ") {
+            return Some(key.to_string());
+        }
+    }
+
+    req.headers()
+        .get("X-API-Key")
+        .and_then(|h| h.to_str().ok())
+        .map(|s| s.to_string())
+}
@@ -1,11 +1,18 @@
 use serde::{Deserialize, Serialize};
 use std::env;
+use std::path::Path;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
     pub server: ServerConfig,
     pub ai_core: AiCoreConfig,
     pub logging: LoggingConfig,
+    #[serde(default)]
+    pub api_keys: ApiKeysConfig,
+    #[serde(default = "ModerationConfig::default_rules_config")]
+    pub moderation: ModerationConfig,
+    #[serde(default)]
+    pub rate_limit: RateLimitConfig,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -28,6 +35,113 @@ pub struct LoggingConfig {
     pub format: String,
 }
 
+/// The set of API keys `auth::ApiKeyAuth` accepts. Empty by default, which leaves the
+/// gateway open (no `ApiKeyAuth`-protected route requires a key) until an operator lists
+/// keys in the config file — see `Config::from_file`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ApiKeysConfig {
+    #[serde(default)]
+    pub keys: Vec<ApiKeyEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiKeyEntry {
+    pub key: String,
+    pub client_id: String,
+    /// Per-key quota override for `RateLimit`. Takes effect when `max_requests` is set,
+    /// with `window_seconds` defaulting to 60 when omitted; a key without `max_requests`
+    /// uses the route's global limit like an anonymous caller would.
+    #[serde(default)]
+    pub max_requests: Option<usize>,
+    #[serde(default)]
+    pub window_seconds: Option<u64>,
+}
+
+impl ApiKeysConfig {
+    pub fn find(&self, key: &str) -> Option<&ApiKeyEntry> {
+        self.keys.iter().find(|entry| entry.key == key)
+    }
+}
+
+/// Policy consumed by `moderation::ModerationPolicy::compile`. Defaults to rejecting
+/// the historical hardcoded keywords, now matched as whole words rather than as
+/// substrings, until operators add their own rules via `Config::from_file`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModerationConfig {
+    #[serde(default = "ModerationConfig::default_rules")]
+    pub rules: Vec<ModerationRuleConfig>,
+    /// Phrases that, when present, suppress a rule match on one of their words (e.g.
+    /// "system requirements" no longer trips a rule written for "system").
+    #[serde(default)]
+    pub allowlist: Vec<String>,
+}
+
+impl ModerationConfig {
+    fn default_rules() -> Vec<ModerationRuleConfig> {
+        ["delete", "drop", "remove", "system", "admin"]
+            .into_iter()
+            .map(|word| ModerationRuleConfig {
+                pattern: word.to_string(),
+                action: ModerationAction::Reject,
+                category: Some(format!("destructive_keyword:{}", word)),
+            })
+            .collect()
+    }
+
+    fn default_rules_config() -> Self {
+        Self { rules: Self::default_rules(), allowlist: Vec::new() }
+    }
+}
+
+impl Default for ModerationConfig {
+    fn default() -> Self {
+        Self::default_rules_config()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModerationRuleConfig {
+    /// Matched case-insensitively on whole tokens (word boundaries), not as a raw
+    /// substring — e.g. "admin" no longer matches inside "administrator".
+    pub pattern: String,
+    pub action: ModerationAction,
+    /// Shown to the caller in the rejection message so they know *why* they were
+    /// blocked. Defaults to the pattern itself when omitted.
+    #[serde(default)]
+    pub category: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ModerationAction {
+    Reject,
+    Warn,
+    Allow,
+}
+
+/// Global quotas `validation::RateLimit` enforces for the `/generate*` and read-only
+/// route groups. Read live off `Config` on every request (see `RateLimitOverride`), so
+/// — unlike the rest of `ServerConfig` — these take effect on the next hot-reload
+/// without a restart, the same as `api_keys` and `moderation`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RateLimitConfig {
+    pub generate_max_requests: usize,
+    pub generate_window_seconds: u64,
+    pub read_max_requests: usize,
+    pub read_window_seconds: u64,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            generate_max_requests: 10,
+            generate_window_seconds: 60,
+            read_max_requests: 120,
+            read_window_seconds: 60,
+        }
+    }
+}
+
 impl Default for Config {
     fn default() -> Self {
         Self {
@@ -45,6 +159,9 @@ impl Default for Config {
                 level: "info".to_string(),
                 format: "json".to_string(),
             },
+            api_keys: ApiKeysConfig::default(),
+            moderation: ModerationConfig::default(),
+            rate_limit: RateLimitConfig::default(),
         }
     }
 }
@@ -94,6 +211,7 @@ impl Config {
         config
     }
     
+    #[allow(dead_code)]
     pub fn server_url(&self) -> String {
         format!("{}:{}", self.server.host, self.server.port)
     }
@@ -105,4 +223,79 @@ impl Config {
     pub fn ai_core_run_url(&self) -> String {
         format!("{}/run", self.ai_core.url)
     }
-} 
\ No newline at end of file
+
+    pub fn ai_core_embed_url(&self) -> String {
+        format!("{}/embed", self.ai_core.url)
+    }
+
+    /// Layers a TOML or JSON config file (chosen by extension, JSON by default) on top
+    /// of the env-derived defaults. Fields the file omits keep their `from_env()` value,
+    /// so operators only need to specify what they're overriding.
+    pub fn from_file(path: &Path) -> anyhow::Result<Self> {
+        let base = serde_json::to_value(Config::from_env())?;
+        let contents = std::fs::read_to_string(path)?;
+
+        let overlay: serde_json::Value = if path.extension().and_then(|ext| ext.to_str()) == Some("toml") {
+            serde_json::to_value(toml::from_str::<toml::Value>(&contents)?)?
+        } else {
+            serde_json::from_str(&contents)?
+        };
+
+        Ok(serde_json::from_value(merge_json(base, overlay))?)
+    }
+}
+
+/// Recursively overlays `overlay` onto `base`, keeping `base` values for keys the
+/// overlay doesn't mention instead of requiring every field to be present.
+fn merge_json(base: serde_json::Value, overlay: serde_json::Value) -> serde_json::Value {
+    match (base, overlay) {
+        (serde_json::Value::Object(mut base_map), serde_json::Value::Object(overlay_map)) => {
+            for (key, value) in overlay_map {
+                let merged = match base_map.remove(&key) {
+                    Some(base_value) => merge_json(base_value, value),
+                    None => value,
+                };
+                base_map.insert(key, merged);
+            }
+            serde_json::Value::Object(base_map)
+        }
+        (_, overlay) => overlay,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merge_json_overlay_wins_on_conflicting_scalars() {
+        let base = serde_json::json!({"server": {"host": "127.0.0.1", "port": 8080}});
+        let overlay = serde_json::json!({"server": {"port": 9090}});
+
+        let merged = merge_json(base, overlay);
+
+        assert_eq!(merged["server"]["port"], 9090);
+        assert_eq!(merged["server"]["host"], "127.0.0.1");
+    }
+
+    #[test]
+    fn merge_json_recurses_into_nested_objects() {
+        let base = serde_json::json!({"a": {"b": {"c": 1, "d": 2}}});
+        let overlay = serde_json::json!({"a": {"b": {"c": 99}}});
+
+        let merged = merge_json(base, overlay);
+
+        assert_eq!(merged["a"]["b"]["c"], 99);
+        assert_eq!(merged["a"]["b"]["d"], 2);
+    }
+
+    #[test]
+    fn merge_json_overlay_replaces_non_object_values_wholesale() {
+        let base = serde_json::json!({"rules": [1, 2, 3]});
+        let overlay = serde_json::json!({"rules": [4]});
+
+        let merged = merge_json(base, overlay);
+
+        assert_eq!(merged["rules"], serde_json::json!([4]));
+    }
+}
@@ -0,0 +1,78 @@
+use arc_swap::ArcSwap;
+use log::{error, info};
+use notify::{RecursiveMode, Watcher};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::config::Config;
+
+/// Watches a config file for changes (filesystem notify, plus `SIGHUP` on Unix) and
+/// atomically swaps `shared` to the newly parsed config. A reload that fails to parse
+/// is logged and discarded, leaving the previous config live.
+pub fn spawn(path: PathBuf, shared: Arc<ArcSwap<Config>>) {
+    reload(&path, &shared);
+    spawn_file_watcher(path.clone(), shared.clone());
+
+    #[cfg(unix)]
+    spawn_sighup_handler(path, shared);
+}
+
+fn reload(path: &Path, shared: &Arc<ArcSwap<Config>>) {
+    match Config::from_file(path) {
+        Ok(config) => {
+            info!("Reloaded configuration from {}", path.display());
+            crate::telemetry::update_log_level(&config.logging);
+            shared.store(Arc::new(config));
+        }
+        Err(e) => error!(
+            "Failed to reload configuration from {}: {} (keeping previous config)",
+            path.display(),
+            e
+        ),
+    }
+}
+
+fn spawn_file_watcher(path: PathBuf, shared: Arc<ArcSwap<Config>>) {
+    std::thread::spawn(move || {
+        let (tx, rx) = std::sync::mpsc::channel();
+        let mut watcher = match notify::recommended_watcher(tx) {
+            Ok(watcher) => watcher,
+            Err(e) => {
+                error!("Failed to start config file watcher: {}", e);
+                return;
+            }
+        };
+
+        if let Err(e) = watcher.watch(&path, RecursiveMode::NonRecursive) {
+            error!("Failed to watch config file {}: {}", path.display(), e);
+            return;
+        }
+
+        for event in rx {
+            match event {
+                Ok(event) if event.kind.is_modify() => reload(&path, &shared),
+                Ok(_) => {}
+                Err(e) => error!("Config watcher error: {}", e),
+            }
+        }
+    });
+}
+
+#[cfg(unix)]
+fn spawn_sighup_handler(path: PathBuf, shared: Arc<ArcSwap<Config>>) {
+    tokio::spawn(async move {
+        let mut sighup = match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::hangup()) {
+            Ok(signal) => signal,
+            Err(e) => {
+                error!("Failed to install SIGHUP handler: {}", e);
+                return;
+            }
+        };
+
+        loop {
+            sighup.recv().await;
+            info!("Received SIGHUP, reloading configuration");
+            reload(&path, &shared);
+        }
+    });
+}
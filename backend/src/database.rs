@@ -1,7 +1,9 @@
 use mongodb::{Client, Database, Collection};
 use mongodb::bson::{doc, Document, to_bson};
+use mongodb::options::{FindOneAndUpdateOptions, ReturnDocument};
 use futures_util::stream::TryStreamExt;
-use crate::models::{ProjectRecord, ProjectQuery, ProjectUpdate};
+use chrono::{DateTime, Utc};
+use crate::models::{ProjectRecord, ProjectQuery, ProjectUpdate, GenerationJob, JobState, SearchResult, WebhookJob};
 use anyhow::Result;
 use log::{info, error, warn};
 
@@ -79,7 +81,11 @@ impl DatabaseService {
         if let Some(metadata) = update.metadata {
             update_doc.insert("metadata", to_bson(&metadata)?);
         }
-        
+
+        if let Some(prompt_embedding) = update.prompt_embedding {
+            update_doc.insert("prompt_embedding", to_bson(&prompt_embedding)?);
+        }
+
         update_doc.insert("updated_at", to_bson(&chrono::Utc::now())?);
         
         let filter = doc! { "project_id": project_id };
@@ -163,6 +169,358 @@ impl DatabaseService {
         }
     }
 
+    /// Raw (untyped) view of the `projects` collection, used by the `migrate-store`
+    /// command to touch documents written before `GeneratedFile` carried a
+    /// `storage_key` instead of inline `content`.
+    pub fn raw_projects_collection(&self) -> Collection<Document> {
+        self.db.collection("projects")
+    }
+
+    pub async fn find_all_raw_projects(&self) -> Result<Vec<Document>> {
+        let collection = self.raw_projects_collection();
+
+        match collection.find(doc! {}, None).await {
+            Ok(cursor) => Ok(cursor.try_collect().await?),
+            Err(e) => {
+                error!("Failed to list raw projects: {}", e);
+                Err(anyhow::anyhow!("Database error: {}", e))
+            }
+        }
+    }
+
+    pub async fn replace_raw_project_files(&self, id: &mongodb::bson::oid::ObjectId, files: Vec<Document>) -> Result<()> {
+        let collection = self.raw_projects_collection();
+
+        let filter = doc! { "_id": id };
+        let update = doc! { "$set": { "files": files } };
+
+        match collection.update_one(filter, update, None).await {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                error!("Failed to migrate project {}: {}", id, e);
+                Err(anyhow::anyhow!("Database error: {}", e))
+            }
+        }
+    }
+
+    pub fn jobs_collection(&self) -> Collection<GenerationJob> {
+        self.db.collection("jobs")
+    }
+
+    pub async fn enqueue_job(&self, job: GenerationJob) -> Result<()> {
+        let collection = self.jobs_collection();
+
+        match collection.insert_one(job, None).await {
+            Ok(result) => {
+                info!("Enqueued generation job: {:?}", result.inserted_id);
+                Ok(())
+            }
+            Err(e) => {
+                error!("Failed to enqueue job: {}", e);
+                Err(anyhow::anyhow!("Database error: {}", e))
+            }
+        }
+    }
+
+    /// Atomically claims the oldest due job, marking it `Running` and owned by `worker_id`.
+    /// The `find_one_and_update` filter/set pair is what makes this safe across workers.
+    pub async fn claim_job(&self, worker_id: &str) -> Result<Option<GenerationJob>> {
+        let collection = self.jobs_collection();
+
+        let filter = doc! {
+            "state": { "$in": ["Queued", "Retry"] },
+            "next_run_at": { "$lte": to_bson(&Utc::now())? },
+        };
+        let update = doc! {
+            "$set": {
+                "state": to_bson(&JobState::Running)?,
+                "locked_by": worker_id,
+                "locked_at": to_bson(&Utc::now())?,
+                "updated_at": to_bson(&Utc::now())?,
+            }
+        };
+        let options = FindOneAndUpdateOptions::builder()
+            .sort(doc! { "next_run_at": 1 })
+            .return_document(ReturnDocument::After)
+            .build();
+
+        match collection.find_one_and_update(filter, update, options).await {
+            Ok(job) => Ok(job),
+            Err(e) => {
+                error!("Failed to claim job: {}", e);
+                Err(anyhow::anyhow!("Database error: {}", e))
+            }
+        }
+    }
+
+    pub async fn reschedule_job(&self, job_id: &str, next_run_at: DateTime<Utc>, attempts: u32) -> Result<()> {
+        let collection = self.jobs_collection();
+
+        let filter = doc! { "job_id": job_id };
+        let update = doc! {
+            "$set": {
+                "state": to_bson(&JobState::Retry)?,
+                "attempts": attempts,
+                "next_run_at": to_bson(&next_run_at)?,
+                "locked_by": Option::<String>::None,
+                "locked_at": Option::<DateTime<Utc>>::None,
+                "updated_at": to_bson(&Utc::now())?,
+            }
+        };
+
+        match collection.update_one(filter, update, None).await {
+            Ok(_) => {
+                warn!("Rescheduled job {} (attempt {})", job_id, attempts);
+                Ok(())
+            }
+            Err(e) => {
+                error!("Failed to reschedule job {}: {}", job_id, e);
+                Err(anyhow::anyhow!("Database error: {}", e))
+            }
+        }
+    }
+
+    pub async fn finish_job(&self, job_id: &str, state: JobState) -> Result<()> {
+        let collection = self.jobs_collection();
+
+        let filter = doc! { "job_id": job_id };
+        let update = doc! {
+            "$set": {
+                "state": to_bson(&state)?,
+                "locked_by": Option::<String>::None,
+                "locked_at": Option::<DateTime<Utc>>::None,
+                "updated_at": to_bson(&Utc::now())?,
+            }
+        };
+
+        match collection.update_one(filter, update, None).await {
+            Ok(_) => {
+                info!("Job {} finished as {:?}", job_id, state);
+                Ok(())
+            }
+            Err(e) => {
+                error!("Failed to finish job {}: {}", job_id, e);
+                Err(anyhow::anyhow!("Database error: {}", e))
+            }
+        }
+    }
+
+    /// Recovers jobs left `Running` by a worker that crashed before finishing or rescheduling them.
+    pub async fn reclaim_stale_jobs(&self, lease: chrono::Duration) -> Result<u64> {
+        let collection = self.jobs_collection();
+
+        let cutoff = Utc::now() - lease;
+        let filter = doc! {
+            "state": to_bson(&JobState::Running)?,
+            "locked_at": { "$lt": to_bson(&cutoff)? },
+        };
+        let update = doc! {
+            "$set": {
+                "state": to_bson(&JobState::Queued)?,
+                "locked_by": Option::<String>::None,
+                "locked_at": Option::<DateTime<Utc>>::None,
+                "updated_at": to_bson(&Utc::now())?,
+            }
+        };
+
+        match collection.update_many(filter, update, None).await {
+            Ok(result) => {
+                if result.modified_count > 0 {
+                    warn!("Reclaimed {} stale job(s) from crashed workers", result.modified_count);
+                }
+                Ok(result.modified_count)
+            }
+            Err(e) => {
+                error!("Failed to reclaim stale jobs: {}", e);
+                Err(anyhow::anyhow!("Database error: {}", e))
+            }
+        }
+    }
+
+    /// Ranks projects by cosine similarity of their `prompt_embedding` to `query_embedding`
+    /// using an Atlas `$vectorSearch` aggregation stage. Requires a vector search index
+    /// named `index_name` to already exist on `prompt_embedding`.
+    pub async fn vector_search_atlas(
+        &self,
+        query_embedding: &[f32],
+        top_k: i64,
+        index_name: &str,
+    ) -> Result<Vec<SearchResult>> {
+        let collection = self.projects_collection();
+
+        let pipeline = vec![
+            doc! {
+                "$vectorSearch": {
+                    "index": index_name,
+                    "path": "prompt_embedding",
+                    "queryVector": query_embedding,
+                    "numCandidates": (top_k * 10).max(100),
+                    "limit": top_k,
+                }
+            },
+            doc! {
+                "$addFields": { "score": { "$meta": "vectorSearchScore" } }
+            },
+        ];
+
+        match collection.clone_with_type::<Document>().aggregate(pipeline, None).await {
+            Ok(cursor) => {
+                let docs: Vec<Document> = cursor.try_collect().await?;
+                let mut results = Vec::with_capacity(docs.len());
+
+                for mut doc in docs {
+                    let score = doc.remove("score").and_then(|v| v.as_f64()).unwrap_or(0.0);
+                    let project: ProjectRecord = mongodb::bson::from_document(doc)?;
+                    results.push(SearchResult { project, score });
+                }
+
+                Ok(results)
+            }
+            Err(e) => {
+                error!("Atlas vector search failed: {}", e);
+                Err(anyhow::anyhow!("Database error: {}", e))
+            }
+        }
+    }
+
+    /// Portable fallback for deployments without Atlas: loads every project carrying an
+    /// embedding and ranks them in Rust. Since embeddings are normalized at insert time,
+    /// cosine similarity reduces to a plain dot product.
+    pub async fn vector_search_fallback(&self, query_embedding: &[f32], top_k: i64) -> Result<Vec<SearchResult>> {
+        let collection = self.projects_collection();
+        let filter = doc! { "prompt_embedding": { "$exists": true, "$ne": null } };
+
+        let cursor = collection.find(filter, None).await.map_err(|e| {
+            error!("Failed to load candidate embeddings: {}", e);
+            anyhow::anyhow!("Database error: {}", e)
+        })?;
+        let candidates: Vec<ProjectRecord> = cursor.try_collect().await?;
+
+        let mut scored: Vec<SearchResult> = candidates
+            .into_iter()
+            .filter_map(|project| {
+                let embedding = project.prompt_embedding.as_ref()?;
+                if embedding.len() != query_embedding.len() {
+                    warn!(
+                        "Skipping project {} with mismatched embedding dimension ({} != {})",
+                        project.project_id, embedding.len(), query_embedding.len()
+                    );
+                    return None;
+                }
+
+                let score: f32 = embedding.iter().zip(query_embedding).map(|(a, b)| a * b).sum();
+                Some(SearchResult { project, score: score as f64 })
+            })
+            .collect();
+
+        scored.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
+        scored.truncate(top_k.max(0) as usize);
+
+        Ok(scored)
+    }
+
+    pub fn webhook_jobs_collection(&self) -> Collection<WebhookJob> {
+        self.db.collection("webhook_jobs")
+    }
+
+    pub async fn enqueue_webhook_job(&self, job: WebhookJob) -> Result<()> {
+        let collection = self.webhook_jobs_collection();
+
+        match collection.insert_one(job, None).await {
+            Ok(result) => {
+                info!("Enqueued webhook delivery: {:?}", result.inserted_id);
+                Ok(())
+            }
+            Err(e) => {
+                error!("Failed to enqueue webhook job: {}", e);
+                Err(anyhow::anyhow!("Database error: {}", e))
+            }
+        }
+    }
+
+    pub async fn claim_webhook_job(&self, worker_id: &str) -> Result<Option<WebhookJob>> {
+        let collection = self.webhook_jobs_collection();
+
+        let filter = doc! {
+            "state": { "$in": ["Queued", "Retry"] },
+            "next_run_at": { "$lte": to_bson(&Utc::now())? },
+        };
+        let update = doc! {
+            "$set": {
+                "state": to_bson(&JobState::Running)?,
+                "locked_by": worker_id,
+                "locked_at": to_bson(&Utc::now())?,
+                "updated_at": to_bson(&Utc::now())?,
+            }
+        };
+        let options = FindOneAndUpdateOptions::builder()
+            .sort(doc! { "next_run_at": 1 })
+            .return_document(ReturnDocument::After)
+            .build();
+
+        match collection.find_one_and_update(filter, update, options).await {
+            Ok(job) => Ok(job),
+            Err(e) => {
+                error!("Failed to claim webhook job: {}", e);
+                Err(anyhow::anyhow!("Database error: {}", e))
+            }
+        }
+    }
+
+    pub async fn reschedule_webhook_job(
+        &self,
+        job_id: &str,
+        next_run_at: DateTime<Utc>,
+        attempts: u32,
+        last_response_status: Option<u16>,
+    ) -> Result<()> {
+        let collection = self.webhook_jobs_collection();
+
+        let filter = doc! { "job_id": job_id };
+        let update = doc! {
+            "$set": {
+                "state": to_bson(&JobState::Retry)?,
+                "attempts": attempts,
+                "next_run_at": to_bson(&next_run_at)?,
+                "last_response_status": last_response_status.map(|s| s as i32),
+                "locked_by": Option::<String>::None,
+                "locked_at": Option::<DateTime<Utc>>::None,
+                "updated_at": to_bson(&Utc::now())?,
+            }
+        };
+
+        match collection.update_one(filter, update, None).await {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                error!("Failed to reschedule webhook job {}: {}", job_id, e);
+                Err(anyhow::anyhow!("Database error: {}", e))
+            }
+        }
+    }
+
+    pub async fn finish_webhook_job(&self, job_id: &str, state: JobState, last_response_status: Option<u16>) -> Result<()> {
+        let collection = self.webhook_jobs_collection();
+
+        let filter = doc! { "job_id": job_id };
+        let update = doc! {
+            "$set": {
+                "state": to_bson(&state)?,
+                "last_response_status": last_response_status.map(|s| s as i32),
+                "locked_by": Option::<String>::None,
+                "locked_at": Option::<DateTime<Utc>>::None,
+                "updated_at": to_bson(&Utc::now())?,
+            }
+        };
+
+        match collection.update_one(filter, update, None).await {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                error!("Failed to finish webhook job {}: {}", job_id, e);
+                Err(anyhow::anyhow!("Database error: {}", e))
+            }
+        }
+    }
+
     #[allow(dead_code)]
     pub async fn get_project_stats(&self) -> Result<serde_json::Value> {
         let collection = self.projects_collection();
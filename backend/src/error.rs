@@ -1,12 +1,19 @@
 use actix_web::{HttpResponse, ResponseError};
 use serde::{Deserialize, Serialize};
 use std::fmt;
+use utoipa::ToSchema;
 
-#[derive(Debug, Serialize, Deserialize)]
+/// Error envelope returned by every `GenesisError` response (400/401/404/408/500/503). The
+/// HTTP status itself follows the `GenesisError` variant; see `ResponseError::error_response`.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct ApiError {
     pub code: String,
     pub message: String,
     pub details: Option<String>,
+    /// The request-id `telemetry::RequestTelemetry` tagged this request's span with, so
+    /// clients and logs can be correlated. `None` outside a request (e.g. a background
+    /// job failure).
+    pub request_id: Option<String>,
     pub timestamp: chrono::DateTime<chrono::Utc>,
 }
 
@@ -16,6 +23,7 @@ impl ApiError {
             code: code.to_string(),
             message: message.to_string(),
             details: None,
+            request_id: crate::telemetry::current_request_id(),
             timestamp: chrono::Utc::now(),
         }
     }
@@ -24,16 +32,25 @@ impl ApiError {
         self.details = Some(details.to_string());
         self
     }
+
+    #[allow(dead_code)]
+    pub fn with_request_id(mut self, request_id: &str) -> Self {
+        self.request_id = Some(request_id.to_string());
+        self
+    }
 }
 
 #[derive(Debug)]
 pub enum GenesisError {
+    #[allow(dead_code)]
     DatabaseError(String),
     AiCoreError(String),
     ValidationError(String),
     TimeoutError(String),
     InternalError(String),
+    #[allow(dead_code)]
     NotFoundError(String),
+    Unauthorized(String),
 }
 
 impl fmt::Display for GenesisError {
@@ -45,6 +62,7 @@ impl fmt::Display for GenesisError {
             GenesisError::TimeoutError(msg) => write!(f, "Timeout error: {}", msg),
             GenesisError::InternalError(msg) => write!(f, "Internal error: {}", msg),
             GenesisError::NotFoundError(msg) => write!(f, "Not found: {}", msg),
+            GenesisError::Unauthorized(msg) => write!(f, "Unauthorized: {}", msg),
         }
     }
 }
@@ -58,11 +76,13 @@ impl ResponseError for GenesisError {
             GenesisError::TimeoutError(_) => (408, "TIMEOUT_ERROR", "Request timed out"),
             GenesisError::InternalError(_) => (500, "INTERNAL_ERROR", "Internal server error"),
             GenesisError::NotFoundError(_) => (404, "NOT_FOUND", "Resource not found"),
+            GenesisError::Unauthorized(_) => (401, "UNAUTHORIZED", "Missing or invalid API key"),
         };
 
         let api_error = ApiError::new(error_code, message)
             .with_details(&self.to_string());
 
+        let status_code = actix_web::http::StatusCode::from_u16(status_code).unwrap_or(actix_web::http::StatusCode::INTERNAL_SERVER_ERROR);
         HttpResponse::build(status_code).json(api_error)
     }
 }
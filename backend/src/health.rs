@@ -3,21 +3,23 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::time::{Duration, Instant};
 use tokio::time::timeout;
+use utoipa::ToSchema;
 
 use crate::config::Config;
-use crate::error::GenesisError;
+use crate::AppState;
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct HealthStatus {
     pub status: String,
     pub timestamp: chrono::DateTime<chrono::Utc>,
     pub version: String,
+    #[schema(value_type = Object)]
     pub uptime: Duration,
     pub services: HashMap<String, ServiceHealth>,
     pub system: SystemInfo,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct ServiceHealth {
     pub status: String,
     pub response_time: Option<f64>,
@@ -25,18 +27,29 @@ pub struct ServiceHealth {
     pub error: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct SystemInfo {
     pub memory_usage: f64,
     pub cpu_usage: f64,
     pub active_connections: usize,
 }
 
-pub async fn health_check(config: web::Data<Config>) -> impl Responder {
+/// Detailed health check covering AI Core and Ollama connectivity, for dashboards and
+/// on-call debugging. `/health` (the main endpoint) is the cheap summary version of
+/// this; prefer that one for automated polling.
+#[utoipa::path(
+    get,
+    path = "/health/detailed",
+    responses(
+        (status = 200, description = "Service and dependency health", body = HealthStatus),
+    ),
+)]
+pub async fn health_check(app_state: web::Data<AppState>) -> impl Responder {
     let start_time = Instant::now();
-    
+    let config = app_state.config.load_full();
+
     let mut services = HashMap::new();
-    
+
     // Check AI Core health
     let ai_core_health = check_ai_core_health(&config).await;
     services.insert("ai_core".to_string(), ai_core_health);
@@ -156,6 +169,12 @@ async fn get_system_info() -> SystemInfo {
     }
 }
 
+/// Kubernetes-style readiness probe: has the service finished starting up.
+#[utoipa::path(
+    get,
+    path = "/health/ready",
+    responses((status = 200, description = "Service is ready to accept traffic")),
+)]
 pub async fn readiness_check() -> impl Responder {
     // Check if the service is ready to accept requests
     HttpResponse::Ok().json(serde_json::json!({
@@ -164,6 +183,12 @@ pub async fn readiness_check() -> impl Responder {
     }))
 }
 
+/// Kubernetes-style liveness probe: is the process still responsive.
+#[utoipa::path(
+    get,
+    path = "/health/live",
+    responses((status = 200, description = "Process is alive")),
+)]
 pub async fn liveness_check() -> impl Responder {
     // Check if the service is alive
     HttpResponse::Ok().json(serde_json::json!({
@@ -1,19 +0,0 @@
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use actix_web::{test, web, App};
-
-    #[actix_web::test]
-    async fn test_health_endpoint() {
-        let app = test::init_service(
-            App::new()
-                .app_data(web::Data::new(AppState::new()))
-                .route("/health", web::get().to(health))
-        ).await;
-
-        let req = test::TestRequest::get().uri("/health").to_request();
-        let resp = test::call_service(&app, req).await;
-        
-        assert!(resp.status().is_success());
-    }
-} 
\ No newline at end of file
@@ -10,37 +10,344 @@ use anyhow::Result as AnyhowResult;
 // Import our modules
 mod models;
 mod database;
+mod queue;
+mod ws;
+mod store;
+mod metrics_mw;
+mod webhook;
+mod validation;
+mod config;
+mod config_watch;
+mod error;
+mod ai_core;
+mod telemetry;
+mod health;
+mod openapi;
+mod auth;
+mod moderation;
 
 use models::*;
 use database::DatabaseService;
+use queue::JobQueue;
+use ws::{ProjectHub, ProjectWs, WsMsg};
+use store::{Store, FileStore, ObjectStore, ObjectStoreConfig};
+use webhook::WebhookQueue;
+use validation::{RateLimiter, RateLimiterBackend, SledRateLimiter};
+use config::Config;
+use error::ApiError;
+use futures_util::StreamExt;
+use arc_swap::ArcSwap;
+use tracing::Instrument;
+use utoipa::OpenApi;
+use utoipa_swagger_ui::SwaggerUi;
+use openapi::ApiDoc;
+
+// How many webhook deliveries may run concurrently per backend instance.
+const WEBHOOK_WORKER_COUNT: usize = 2;
+// How often stale rate-limit entries are swept from memory.
+const RATE_LIMIT_SWEEP_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60);
+
+// How many generations may run concurrently before jobs queue up waiting for a permit.
+const MAX_CONCURRENT_GENERATIONS: usize = 4;
+// How many attempts a job gets in total (the first run plus retries with exponential
+// backoff) before it is marked Failed.
+const MAX_JOB_ATTEMPTS: u32 = 5;
 
 // App state with MongoDB
 struct AppState {
     db: Arc<DatabaseService>,
+    queue: Arc<JobQueue>,
+    hub: Arc<ProjectHub>,
+    store: Arc<dyn Store>,
+    webhooks: Arc<WebhookQueue>,
+    config: Arc<ArcSwap<Config>>,
 }
 
 impl AppState {
     async fn new() -> AnyhowResult<Self> {
+        let config = Arc::new(ArcSwap::new(Arc::new(Config::from_env())));
+
+        if let Ok(path) = std::env::var("GENESIS_CONFIG_FILE") {
+            config_watch::spawn(std::path::PathBuf::from(path), config.clone());
+        }
+
         let connection_string = std::env::var("MONGODB_URI")
             .unwrap_or_else(|_| "mongodb://localhost:27017".to_string());
         let database_name = std::env::var("MONGODB_DB")
             .unwrap_or_else(|_| "genesis".to_string());
-        
-        let db = DatabaseService::new(&connection_string, &database_name).await?;
-        
+
+        let db = Arc::new(DatabaseService::new(&connection_string, &database_name).await?);
+        let queue = Arc::new(JobQueue::new(db.clone(), MAX_CONCURRENT_GENERATIONS, MAX_JOB_ATTEMPTS));
+        let store = build_store().await?;
+        let webhooks = Arc::new(WebhookQueue::new(db.clone()));
+
         Ok(Self {
-            db: Arc::new(db),
+            db,
+            queue,
+            hub: Arc::new(ProjectHub::new()),
+            store,
+            webhooks,
+            config,
         })
     }
 }
 
+/// Picks a `sled`-backed rate limiter (surviving restarts) when `sled_db` is available,
+/// otherwise falls back to the in-memory `RateLimiter`.
+fn build_rate_limiter(
+    max_requests: usize,
+    window_duration: std::time::Duration,
+    sled_db: Option<&sled::Db>,
+    tree_name: &str,
+) -> Arc<dyn RateLimiterBackend> {
+    if let Some(db) = sled_db {
+        match SledRateLimiter::new(db, tree_name, max_requests, window_duration) {
+            Ok(limiter) => return Arc::new(limiter),
+            Err(e) => error!("Failed to open sled rate-limit tree {}: {} (falling back to in-memory)", tree_name, e),
+        }
+    }
+
+    Arc::new(RateLimiter::new(max_requests, window_duration))
+}
+
+/// Live quota for the `/generate*` scope, re-read from `Config` on every request by
+/// `validation::RateLimit` so `rate_limit.generate_*` can be tuned via hot-reload.
+fn generate_rate_limit(config: &Config) -> validation::RateLimitOverride {
+    validation::RateLimitOverride {
+        max_requests: config.rate_limit.generate_max_requests,
+        window_duration: std::time::Duration::from_secs(config.rate_limit.generate_window_seconds),
+    }
+}
+
+/// Live quota for the read-only scope; see `generate_rate_limit`.
+fn read_rate_limit(config: &Config) -> validation::RateLimitOverride {
+    validation::RateLimitOverride {
+        max_requests: config.rate_limit.read_max_requests,
+        window_duration: std::time::Duration::from_secs(config.rate_limit.read_window_seconds),
+    }
+}
+
+/// Picks an S3-compatible store when `GENESIS_S3_BUCKET` is configured, otherwise
+/// falls back to the local filesystem under `GENESIS_STORAGE_DIR`.
+async fn build_store() -> AnyhowResult<Arc<dyn Store>> {
+    if let Some(config) = ObjectStoreConfig::from_env() {
+        info!("Using S3-compatible object store (bucket: {})", config.bucket);
+        return Ok(Arc::new(ObjectStore::new(config).await?));
+    }
+
+    let base_dir = std::env::var("GENESIS_STORAGE_DIR").unwrap_or_else(|_| "./storage".to_string());
+    info!("Using local file store at {}", base_dir);
+    Ok(Arc::new(FileStore::new(base_dir)))
+}
+
+/// Calls the AI core's embedding endpoint and L2-normalizes the result so the vector
+/// fallback search can rank by a plain dot product instead of a full cosine division.
+async fn embed_prompt(prompt: &str, config: &Config) -> AnyhowResult<Vec<f32>> {
+    let client = reqwest::Client::new();
+    let embed_url = config.ai_core_embed_url();
+    let ai_core_span = tracing::info_span!("ai_core_embed");
+    let response = ai_core::request_with_retry(&config.ai_core, || {
+        client
+            .post(&embed_url)
+            .json(&serde_json::json!({ "text": prompt }))
+            .timeout(std::time::Duration::from_secs(30))
+            .send()
+    })
+    .instrument(ai_core_span)
+    .await
+    .map_err(|e| anyhow::anyhow!(e.to_string()))?;
+
+    let data = response.json::<serde_json::Value>().await?;
+    let mut embedding: Vec<f32> = data
+        .get("data")
+        .and_then(|d| d.get("embedding"))
+        .and_then(|e| e.as_array())
+        .ok_or_else(|| anyhow::anyhow!("AI core embedding response missing 'data.embedding'"))?
+        .iter()
+        .filter_map(|v| v.as_f64())
+        .map(|v| v as f32)
+        .collect();
+
+    let norm = embedding.iter().map(|v| v * v).sum::<f32>().sqrt();
+    if norm > 0.0 {
+        for value in embedding.iter_mut() {
+            *value /= norm;
+        }
+    }
+
+    Ok(embedding)
+}
+
+async fn search_projects(
+    data: web::Json<SearchRequest>,
+    app_state: web::Data<AppState>,
+) -> Result<HttpResponse> {
+    let config = app_state.config.load_full();
+    let query_embedding = match embed_prompt(&data.query, &config).await {
+        Ok(embedding) => embedding,
+        Err(e) => {
+            error!("Failed to embed search query: {}", e);
+            return Ok(HttpResponse::InternalServerError().json(ApiResponse::<Vec<SearchResult>> {
+                success: false,
+                message: "Failed to embed search query".to_string(),
+                data: None,
+            }));
+        }
+    };
+
+    let top_k = data.top_k.unwrap_or(10);
+    let use_atlas = std::env::var("GENESIS_VECTOR_SEARCH_ENGINE")
+        .map(|v| v == "atlas")
+        .unwrap_or(false);
+
+    let results = if use_atlas {
+        let index_name = std::env::var("GENESIS_VECTOR_INDEX").unwrap_or_else(|_| "prompt_embedding_index".to_string());
+        app_state.db.vector_search_atlas(&query_embedding, top_k, &index_name).await
+    } else {
+        app_state.db.vector_search_fallback(&query_embedding, top_k).await
+    };
+
+    match results {
+        Ok(results) => Ok(HttpResponse::Ok().json(ApiResponse {
+            success: true,
+            message: "Search completed".to_string(),
+            data: Some(results),
+        })),
+        Err(e) => {
+            error!("Vector search failed: {}", e);
+            Ok(HttpResponse::InternalServerError().json(ApiResponse::<Vec<SearchResult>> {
+                success: false,
+                message: "Search failed".to_string(),
+                data: None,
+            }))
+        }
+    }
+}
+
+async fn metrics_endpoint(handle: web::Data<metrics_exporter_prometheus::PrometheusHandle>) -> Result<HttpResponse> {
+    Ok(HttpResponse::Ok()
+        .content_type("text/plain; version=0.0.4")
+        .body(handle.render()))
+}
+
+/// Parses a single-range `Range: bytes=start-end` header against a known total file
+/// size, clamping an open-ended `start-` to the end of the file. Returns `None` for
+/// anything we don't support (multi-range, non-`bytes` units, unparseable numbers) so the
+/// caller falls back to a full `200 OK` response, and `Some(Err(()))` for a range that
+/// is syntactically a byte-range but unsatisfiable against `size` (`416`).
+fn parse_range_header(header: &str, size: u64) -> Option<Result<store::ByteRange, ()>> {
+    let spec = header.strip_prefix("bytes=")?;
+    if spec.contains(',') {
+        return None; // multi-range requests aren't supported; serve the whole file
+    }
+
+    let (start, end) = spec.split_once('-')?;
+    let parsed = if start.is_empty() {
+        // "bytes=-N" means the last N bytes of the file.
+        let suffix_len: u64 = end.parse().ok()?;
+        let suffix_len = suffix_len.min(size);
+        (size.saturating_sub(suffix_len), size.saturating_sub(1))
+    } else {
+        let start: u64 = start.parse().ok()?;
+        let end = if end.is_empty() { size.saturating_sub(1) } else { end.parse().ok()? };
+        (start, end)
+    };
+
+    if parsed.0 > parsed.1 || parsed.0 >= size {
+        return Some(Err(()));
+    }
+
+    Some(Ok(store::ByteRange { start: parsed.0, end: parsed.1.min(size.saturating_sub(1)) }))
+}
+
+async fn get_project_file(
+    req: actix_web::HttpRequest,
+    path: web::Path<(String, String)>,
+    app_state: web::Data<AppState>,
+) -> Result<HttpResponse> {
+    let (project_id, name) = path.into_inner();
+
+    let project = match app_state.db.get_project(&project_id).await {
+        Ok(Some(project)) => project,
+        Ok(None) => return Ok(HttpResponse::NotFound().finish()),
+        Err(e) => {
+            error!("Failed to load project {}: {}", project_id, e);
+            return Ok(HttpResponse::InternalServerError().finish());
+        }
+    };
+
+    let file = match project.files.iter().find(|f| f.name == name) {
+        Some(file) => file,
+        None => return Ok(HttpResponse::NotFound().finish()),
+    };
+
+    if file.storage_key.is_empty() {
+        // Pre-`migrate-store` document: the bytes were never offloaded to `Store`.
+        error!("File {} for project {} has no storage_key; run migrate-store", name, project_id);
+        return Ok(HttpResponse::ServiceUnavailable().finish());
+    }
+
+    let range = match (req.headers().get("Range").and_then(|h| h.to_str().ok()), file.size) {
+        (Some(header), Some(size)) => match parse_range_header(header, size) {
+            Some(Ok(range)) => Some(range),
+            Some(Err(())) => {
+                return Ok(HttpResponse::RangeNotSatisfiable()
+                    .insert_header(("Content-Range", format!("bytes */{}", size)))
+                    .finish());
+            }
+            None => None,
+        },
+        _ => None,
+    };
+
+    match app_state.store.load(&file.storage_key, range).await {
+        Ok(stream) => {
+            let mut response = match range {
+                Some(_) => HttpResponse::PartialContent(),
+                None => HttpResponse::Ok(),
+            };
+            response.insert_header(("Accept-Ranges", "bytes"));
+
+            if let Some(size) = file.size {
+                match range {
+                    Some(range) => {
+                        response.insert_header(("Content-Length", (range.end - range.start + 1).to_string()));
+                        response.insert_header(("Content-Range", format!("bytes {}-{}/{}", range.start, range.end, size)));
+                    }
+                    None => {
+                        response.insert_header(("Content-Length", size.to_string()));
+                    }
+                }
+            }
+
+            Ok(response.streaming(stream))
+        }
+        Err(e) => {
+            error!("Failed to load file {} for project {}: {}", name, project_id, e);
+            Ok(HttpResponse::InternalServerError().finish())
+        }
+    }
+}
+
+async fn project_ws(
+    req: actix_web::HttpRequest,
+    stream: web::Payload,
+    path: web::Path<String>,
+    app_state: web::Data<AppState>,
+) -> Result<HttpResponse> {
+    let project_id = path.into_inner();
+    let actor = ProjectWs::new(project_id, app_state.db.clone(), app_state.hub.clone());
+    actix_web_actors::ws::start(actor, &req, stream)
+}
+
 // API endpoints
-async fn health() -> Result<HttpResponse> {
+async fn health(app_state: web::Data<AppState>) -> Result<HttpResponse> {
     let mut services = HashMap::new();
     services.insert("backend".to_string(), "healthy".to_string());
-    
-    // Check AI core connectivity
-    let ai_core_status = check_ai_core_health().await;
+
+    // Check AI core connectivity against the latest live-reloaded config
+    let config = app_state.config.load_full();
+    let ai_core_status = check_ai_core_health(&config).await;
     services.insert("ai_core".to_string(), ai_core_status);
     
     let response = HealthResponse {
@@ -60,31 +367,51 @@ async fn generate_project(
     data: web::Json<GenerateRequest>,
     app_state: web::Data<AppState>,
 ) -> Result<HttpResponse> {
+    let config = app_state.config.load_full();
+
+    // Run the same length/regex validation and content moderation `/generate/stream`
+    // does — this is the primary generation path, so it needs the configurable
+    // moderation filter applied just as much as the streaming one.
+    validation::validate_generate_request(
+        web::Json(validation::GenerateRequest { prompt: data.prompt.clone(), backend: data.backend.clone() }),
+        &config,
+    )
+    .await?;
+
     let project_id = Uuid::new_v4().to_string();
-    
+
     info!("Starting project generation: {}", project_id);
-    
+
     // Create initial project record
-    let project_record = ProjectRecord::new(
+    let mut project_record = ProjectRecord::new(
         project_id.clone(),
         data.prompt.clone(),
         data.backend.clone().unwrap_or_else(|| "ollama".to_string()),
     );
-    
+    project_record.callback_url = data.callback_url.clone();
+    project_record.callback_secret = data.callback_secret.clone();
+
     // Store in MongoDB
     let db = app_state.db.clone();
     match db.create_project(project_record).await {
         Ok(_) => {
-            // Start async generation process
-            let app_state_clone = app_state.clone();
-            let project_id_clone = project_id.clone();
-            let prompt_clone = data.prompt.clone();
-            let backend_clone = data.backend.clone().unwrap_or_else(|| "ollama".to_string());
-            
-            tokio::spawn(async move {
-                generate_project_async(project_id_clone, prompt_clone, backend_clone, app_state_clone).await;
-            });
-            
+            // Enqueue the generation job; a worker will pick it up and retry on failure
+            let backend = data.backend.clone().unwrap_or_else(|| "ollama".to_string());
+
+            match app_state.queue.enqueue(project_id.clone(), data.prompt.clone(), backend).await {
+                Ok(job_id) => {
+                    info!("Enqueued generation job {} for project {}", job_id, project_id);
+                }
+                Err(e) => {
+                    error!("Failed to enqueue generation job for project {}: {}", project_id, e);
+                    return Ok(HttpResponse::InternalServerError().json(ApiResponse::<String> {
+                        success: false,
+                        message: "Failed to start project generation".to_string(),
+                        data: None,
+                    }));
+                }
+            }
+
             Ok(HttpResponse::Accepted().json(ApiResponse {
                 success: true,
                 message: "Project generation started".to_string(),
@@ -102,6 +429,83 @@ async fn generate_project(
     }
 }
 
+/// Streams the AI core's `/run` response straight through to the client as Server-Sent
+/// Events instead of buffering the whole generation before replying. Unlike
+/// `generate_project`, this bypasses the job queue entirely: the caller holds the
+/// connection open for the duration of the run, so there's nothing to enqueue or retry.
+#[utoipa::path(
+    post,
+    path = "/generate/stream",
+    request_body = validation::GenerateRequest,
+    responses(
+        (status = 200, description = "SSE stream with one `data:` frame per upstream chunk; an upstream error mid-stream produces an `event: error` frame carrying an `ApiError`", content_type = "text/event-stream"),
+        (status = 400, description = "Validation error (prompt length or backend enum)", body = crate::error::ApiError),
+        (status = 429, description = "Rate limit exceeded; JSON body with `error`, `remaining_requests` and `retry_after` plus `X-RateLimit-*`/`Retry-After` headers"),
+        (status = 502, description = "AI Core unavailable or returned an error", body = crate::error::ApiError),
+    ),
+)]
+async fn generate_project_stream(
+    data: web::Json<validation::GenerateRequest>,
+    app_state: web::Data<AppState>,
+) -> Result<HttpResponse> {
+    let config = app_state.config.load_full();
+    let data = validation::validate_generate_request(data, &config).await?;
+
+    let project_id = Uuid::new_v4().to_string();
+    info!("Starting streaming generation: {}", project_id);
+    let client = reqwest::Client::new();
+    let run_url = config.ai_core_run_url();
+    let request_body = serde_json::json!({
+        "prompt": data.prompt,
+        "backend": data.backend.clone().unwrap_or_else(|| "ollama".to_string()),
+        "stream": true,
+    });
+    let timeout = std::time::Duration::from_secs(config.ai_core.timeout_seconds);
+
+    let ai_core_span = tracing::info_span!("ai_core_run", project_id = %project_id, stream = true);
+    let upstream = ai_core::request_with_retry(&config.ai_core, || {
+        client.post(&run_url).json(&request_body).timeout(timeout).send()
+    })
+    .instrument(ai_core_span)
+    .await;
+
+    let upstream = match upstream {
+        Ok(response) if response.status().is_success() => response,
+        Ok(response) => {
+            let status = response.status();
+            error!("AI core returned error status opening stream for {}: {}", project_id, status);
+            return Ok(HttpResponse::BadGateway().json(ApiError::new(
+                "AI_CORE_ERROR",
+                &format!("AI core returned error status: {}", status),
+            )));
+        }
+        Err(e) => {
+            error!("Failed to open AI core stream for {}: {}", project_id, e);
+            return Ok(HttpResponse::BadGateway().json(
+                ApiError::new("AI_CORE_ERROR", "Failed to connect to AI Core").with_details(&e.to_string()),
+            ));
+        }
+    };
+
+    let sse_stream = upstream.bytes_stream().map(|chunk| {
+        let frame = match chunk {
+            Ok(bytes) => format!("data: {}\n\n", String::from_utf8_lossy(&bytes)),
+            Err(e) => {
+                let api_error =
+                    ApiError::new("AI_CORE_STREAM_ERROR", "AI Core stream interrupted").with_details(&e.to_string());
+                let payload = serde_json::to_string(&api_error).unwrap_or_else(|_| "{}".to_string());
+                format!("event: error\ndata: {}\n\n", payload)
+            }
+        };
+        Ok::<_, actix_web::Error>(web::Bytes::from(frame))
+    });
+
+    Ok(HttpResponse::Ok()
+        .content_type("text/event-stream")
+        .insert_header(("Cache-Control", "no-cache"))
+        .streaming(sse_stream))
+}
+
 async fn get_projects(app_state: web::Data<AppState>) -> Result<HttpResponse> {
     let db = app_state.db.clone();
     let query = ProjectQuery {
@@ -164,14 +568,20 @@ async fn get_project(
 }
 
 // Helper functions
-async fn check_ai_core_health() -> String {
+async fn check_ai_core_health(config: &Config) -> String {
     let client = reqwest::Client::new();
-    match client
-        .get("http://127.0.0.1:8000/health")
-        .timeout(std::time::Duration::from_secs(10))
-        .send()
-        .await
-    {
+    let url = config.ai_core_health_url();
+    let ai_core_span = tracing::info_span!("ai_core_health_check");
+    let result = ai_core::request_with_retry(&config.ai_core, || {
+        client
+            .get(&url)
+            .timeout(std::time::Duration::from_secs(10))
+            .send()
+    })
+    .instrument(ai_core_span)
+    .await;
+
+    match result {
         Ok(response) => {
             if response.status().is_success() {
                 match response.json::<serde_json::Value>().await {
@@ -195,73 +605,96 @@ async fn check_ai_core_health() -> String {
     }
 }
 
-async fn generate_project_async(
-    project_id: String,
-    prompt: String,
-    backend: String,
-    app_state: web::Data<AppState>,
-) {
-    // Update status to generating
+/// Runs a single attempt of a generation job: marks the project `Generating`, calls the
+/// AI core, and persists the results. Returns `Err` on any failure so the caller (the
+/// job queue) can decide whether to retry or give up, rather than marking the project
+/// `Failed` itself.
+async fn process_generation_job(
+    job: &GenerationJob,
+    app_state: &web::Data<AppState>,
+) -> std::result::Result<(), String> {
     let db = app_state.db.clone();
     let update = ProjectUpdate {
         status: Some(ProjectStatus::Generating),
         files: None,
         output: None,
         metadata: None,
+        prompt_embedding: None,
     };
-    
-    if let Err(e) = db.update_project(&project_id, update).await {
-        error!("Failed to update project status to generating: {}", e);
-        return;
+
+    db.update_project(&job.project_id, update)
+        .await
+        .map_err(|e| format!("Failed to update project status to generating: {}", e))?;
+    app_state.hub.publish(&job.project_id, WsMsg::StatusChanged(ProjectStatus::Generating));
+
+    info!("Generating project {} with prompt: {}", job.project_id, job.prompt);
+
+    let config = app_state.config.load_full();
+
+    // Best-effort: embed the prompt so /projects/search can find this project later.
+    // Done here rather than inline in the /generate handler so the AI-core round-trip
+    // for the embedding doesn't add to that endpoint's response latency.
+    match embed_prompt(&job.prompt, &config).await {
+        Ok(embedding) => {
+            let embedding_update = ProjectUpdate {
+                status: None,
+                files: None,
+                output: None,
+                metadata: None,
+                prompt_embedding: Some(embedding),
+            };
+            if let Err(e) = db.update_project(&job.project_id, embedding_update).await {
+                error!("Failed to save prompt embedding for project {}: {}", job.project_id, e);
+            }
+        }
+        Err(e) => error!("Failed to embed prompt for project {}: {}", job.project_id, e),
     }
-    
-    info!("Generating project {} with prompt: {}", project_id, prompt);
-    
-    // Call AI core
+
+    // Call AI core, retrying transient failures per AiCoreConfig::max_retries
     let client = reqwest::Client::new();
     let request_body = serde_json::json!({
-        "prompt": prompt,
-        "backend": backend
+        "prompt": job.prompt,
+        "backend": job.backend
     });
-    
-    match client
-        .post("http://127.0.0.1:8000/run")
-        .json(&request_body)
-        .timeout(std::time::Duration::from_secs(300))
-        .send()
-        .await
-    {
-        Ok(response) => {
-            if response.status().is_success() {
-                match response.json::<serde_json::Value>().await {
-                    Ok(data) => {
-                        update_project_with_results(project_id, data, app_state).await;
-                    }
-                    Err(e) => {
-                        error!("Failed to parse AI core response: {}", e);
-                        mark_project_failed(project_id, "Failed to parse AI core response".to_string(), app_state).await;
-                    }
-                }
-            } else {
-                error!("AI core returned error status: {}", response.status());
-                mark_project_failed(project_id, "AI core returned error".to_string(), app_state).await;
-            }
-        }
-        Err(e) => {
-            error!("Failed to call AI core: {}", e);
-            mark_project_failed(project_id, "Failed to connect to AI core".to_string(), app_state).await;
-        }
+    let run_url = config.ai_core_run_url();
+    let timeout = std::time::Duration::from_secs(config.ai_core.timeout_seconds);
+
+    let ai_core_span = tracing::info_span!("ai_core_run", project_id = %job.project_id, backend = %job.backend);
+    let run_started = std::time::Instant::now();
+    let response = ai_core::request_with_retry(&config.ai_core, || {
+        client.post(&run_url).json(&request_body).timeout(timeout).send()
+    })
+    .instrument(ai_core_span)
+    .await
+    .map_err(|e| {
+        metrics::increment_counter!("genesis_ai_core_failures_total");
+        format!("Failed to connect to AI core: {}", e)
+    })?;
+    metrics::histogram!("genesis_generation_duration_seconds", run_started.elapsed().as_secs_f64());
+
+    if !response.status().is_success() {
+        metrics::increment_counter!("genesis_ai_core_failures_total");
+        return Err(format!("AI core returned error status: {}", response.status()));
     }
+
+    let data = response
+        .json::<serde_json::Value>()
+        .await
+        .map_err(|e| format!("Failed to parse AI core response: {}", e))?;
+
+    update_project_with_results(job.project_id.clone(), data, app_state).await
 }
 
 async fn update_project_with_results(
     project_id: String,
     data: serde_json::Value,
-    app_state: web::Data<AppState>,
-) {
+    app_state: &web::Data<AppState>,
+) -> std::result::Result<(), String> {
     let db = app_state.db.clone();
-    
-    // Parse files from AI core response
+
+    // Parse files from AI core response, offloading each one's content to the
+    // configured Store immediately so the project document only ever holds the
+    // small storage_key rather than the full text (MongoDB caps documents at 16MB).
     let mut files = Vec::new();
     if let Some(files_array) = data.get("data").and_then(|d| d.get("files")).and_then(|f| f.as_array()) {
         for file in files_array {
@@ -270,39 +703,102 @@ async fn update_project_with_results(
                 file.get("content").and_then(|c| c.as_str()),
                 file.get("language").and_then(|l| l.as_str()),
             ) {
-                files.push(GeneratedFile {
+                let key = format!("{}/{}", project_id, name);
+                let size = content.len() as u64;
+
+                if let Err(e) = app_state.store.save(bytes::Bytes::copy_from_slice(content.as_bytes()), &key).await {
+                    return Err(format!("Failed to store file {}: {}", name, e));
+                }
+
+                let file = GeneratedFile {
                     name: name.to_string(),
-                    content: content.to_string(),
+                    storage_key: key,
                     language: language.to_string(),
-                    size: None,
-                    last_modified: None,
-                });
+                    size: Some(size),
+                    last_modified: Some(Utc::now()),
+                };
+                app_state.hub.publish(&project_id, WsMsg::FileGenerated(file.clone()));
+                files.push(file);
             }
         }
     }
-    
+
     // Parse output
     let output = data.get("data")
         .and_then(|d| d.get("output"))
         .and_then(|o| o.as_str())
         .unwrap_or("")
         .to_string();
-    
+
+    if !output.is_empty() {
+        app_state.hub.publish(&project_id, WsMsg::OutputChunk(output.clone()));
+    }
+
     let update = ProjectUpdate {
         status: Some(ProjectStatus::Completed),
         files: Some(files),
         output: Some(output),
         metadata: None,
+        prompt_embedding: None,
     };
-    
+
     match db.update_project(&project_id, update).await {
         Ok(_) => {
             info!("Project {} completed successfully", project_id);
+            app_state.hub.publish(&project_id, WsMsg::StatusChanged(ProjectStatus::Completed));
+            app_state.hub.publish(&project_id, WsMsg::Completed);
+            metrics::increment_counter!("genesis_projects_total", "status" => "completed");
+            enqueue_completion_webhook(&project_id, "completed", app_state).await;
+            Ok(())
         }
+        Err(e) => Err(format!("Failed to update project with results: {}", e)),
+    }
+}
+
+/// Builds and enqueues the completion webhook for `project_id`, if it was created
+/// with a `callback_url`. Delivery (and signing) happens asynchronously through the
+/// `WebhookQueue` so a slow or flaky receiver never blocks the generation pipeline.
+async fn enqueue_completion_webhook(project_id: &str, status: &str, app_state: &web::Data<AppState>) {
+    let project = match app_state.db.get_project(project_id).await {
+        Ok(Some(project)) => project,
+        Ok(None) => return,
+        Err(e) => {
+            error!("Failed to load project {} for webhook delivery: {}", project_id, e);
+            return;
+        }
+    };
+
+    let Some(url) = project.callback_url.clone() else {
+        return;
+    };
+
+    let manifest: Vec<_> = project.files.iter().map(|f| serde_json::json!({
+        "name": f.name,
+        "language": f.language,
+        "size": f.size,
+    })).collect();
+
+    let payload = serde_json::json!({
+        "project_id": project_id,
+        "status": status,
+        "files": manifest,
+        "timestamp": Utc::now(),
+    });
+
+    let payload = match serde_json::to_string(&payload) {
+        Ok(payload) => payload,
         Err(e) => {
-            error!("Failed to update project with results: {}", e);
-            mark_project_failed(project_id, "Failed to update project with results".to_string(), app_state).await;
+            error!("Failed to serialize webhook payload for project {}: {}", project_id, e);
+            return;
         }
+    };
+
+    if let Err(e) = app_state
+        .webhooks
+        .enqueue(project_id.to_string(), url, project.callback_secret.clone(), payload)
+        .await
+    {
+        error!("Failed to enqueue webhook for project {}: {}", project_id, e);
     }
 }
 
@@ -317,47 +813,227 @@ async fn mark_project_failed(
         files: None,
         output: Some(error_message.clone()),
         metadata: None,
+        prompt_embedding: None,
     };
     
     if let Err(e) = db.update_project(&project_id, update).await {
         error!("Failed to mark project as failed: {}", e);
         return;
     }
-    
+
+    app_state.hub.publish(&project_id, WsMsg::StatusChanged(ProjectStatus::Failed));
+    app_state.hub.publish(&project_id, WsMsg::Failed(error_message.clone()));
+    metrics::increment_counter!("genesis_projects_total", "status" => "failed");
+    enqueue_completion_webhook(&project_id, "failed", &app_state).await;
     info!("Project {} failed: {}", project_id, error_message);
 }
 
+/// One-shot `migrate-store` command: walks every project, offloads any file still
+/// carrying inline `content` into the configured `Store`, and rewrites the document
+/// to hold `storage_key` instead. Safe to re-run; already-migrated files are skipped.
+async fn run_migrate_store(app_state: &web::Data<AppState>) -> AnyhowResult<()> {
+    let projects = app_state.db.find_all_raw_projects().await?;
+    let mut migrated_files = 0usize;
+
+    for project in projects {
+        let id = match project.get_object_id("_id") {
+            Ok(id) => id,
+            Err(_) => continue,
+        };
+        let project_id = project.get_str("project_id").unwrap_or_default().to_string();
+        let files = project.get_array("files").cloned().unwrap_or_default();
+
+        let mut changed = false;
+        let mut migrated = Vec::with_capacity(files.len());
+
+        for file in files {
+            let mut file_doc = match file.as_document() {
+                Some(doc) => doc.clone(),
+                None => continue,
+            };
+
+            if let Ok(content) = file_doc.get_str("content").map(|s| s.to_string()) {
+                let name = file_doc.get_str("name").unwrap_or("file").to_string();
+                let key = format!("{}/{}", project_id, name);
+
+                app_state
+                    .store
+                    .save(bytes::Bytes::copy_from_slice(content.as_bytes()), &key)
+                    .await?;
+
+                file_doc.remove("content");
+                file_doc.insert("storage_key", key);
+                file_doc.insert("size", content.len() as i64);
+                changed = true;
+            }
+
+            migrated.push(file_doc);
+        }
+
+        if changed {
+            app_state.db.replace_raw_project_files(&id, migrated).await?;
+            migrated_files += 1;
+            info!("Migrated project {} to the configured store", project_id);
+        }
+    }
+
+    info!("migrate-store complete: {} project(s) migrated", migrated_files);
+    Ok(())
+}
+
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
-    env_logger::init();
-    
+    telemetry::init(&Config::from_env().logging);
+
     let app_state = match AppState::new().await {
         Ok(state) => web::Data::new(state),
         Err(e) => {
             error!("Failed to initialize database: {}", e);
-            return Err(std::io::Error::new(std::io::ErrorKind::Other, e));
+            return Err(std::io::Error::other(e));
         }
     };
-    
+
+    if std::env::args().any(|arg| arg == "migrate-store") {
+        return run_migrate_store(&app_state)
+            .await
+            .map_err(std::io::Error::other);
+    }
+
+
+    // Recover jobs left `Running` by a worker that crashed mid-generation, then start
+    // the pool that claims and processes queued jobs.
+    app_state.queue.reclaim_crashed_workers().await;
+    app_state.queue.clone().spawn_workers(app_state.clone(), MAX_CONCURRENT_GENERATIONS);
+    app_state.webhooks.clone().spawn_workers(WEBHOOK_WORKER_COUNT);
+
+    let metrics_handle = web::Data::new(metrics_mw::install_recorder());
+
+    // `/generate` kicks off an expensive 300s AI-core call, so it gets a tight bucket;
+    // reads can be much more permissive. The actual quota enforced on each request is
+    // read live from `Config.rate_limit` (see `RateLimit::new` below) — these starting
+    // values just size the backend and its `sweep` retention window.
+    let rate_limit_db = std::env::var("GENESIS_RATE_LIMIT_DB").ok().and_then(|path| match sled::open(&path) {
+        Ok(db) => Some(db),
+        Err(e) => {
+            error!("Failed to open sled rate-limit db at {}: {} (falling back to in-memory limiting)", path, e);
+            None
+        }
+    });
+    let rate_limit_config = app_state.config.load_full().rate_limit.clone();
+    let generate_limiter = build_rate_limiter(
+        rate_limit_config.generate_max_requests,
+        std::time::Duration::from_secs(rate_limit_config.generate_window_seconds),
+        rate_limit_db.as_ref(),
+        "rate_limit_generate",
+    );
+    let read_limiter = build_rate_limiter(
+        rate_limit_config.read_max_requests,
+        std::time::Duration::from_secs(rate_limit_config.read_window_seconds),
+        rate_limit_db.as_ref(),
+        "rate_limit_read",
+    );
+
+    for limiter in [generate_limiter.clone(), read_limiter.clone()] {
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(RATE_LIMIT_SWEEP_INTERVAL).await;
+                limiter.sweep();
+            }
+        });
+    }
+
     info!("Starting Genesis Backend server on http://127.0.0.1:8080");
-    
+
     HttpServer::new(move || {
         let cors = Cors::default()
             .allow_any_origin()
             .allow_any_method()
             .allow_any_header()
             .max_age(3600);
-        
+
         App::new()
             .wrap(cors)
             .app_data(app_state.clone())
+            .app_data(metrics_handle.clone())
             .wrap(Logger::default())
-            .route("/health", web::get().to(health))
-            .route("/generate", web::post().to(generate_project))
-            .route("/projects", web::get().to(get_projects))
-            .route("/projects/{id}", web::get().to(get_project))
+            .wrap(telemetry::RequestTelemetry)
+            .wrap(metrics_mw::Metrics)
+            .route("/metrics", web::get().to(metrics_endpoint))
+            .route("/openapi.json", web::get().to(|| async { HttpResponse::Ok().json(ApiDoc::openapi()) }))
+            .service(SwaggerUi::new("/docs/{_:.*}").url("/openapi.json", ApiDoc::openapi()))
+            // A bare `web::scope("")` claims every request by its empty prefix and answers
+            // 404 itself when none of its own routes match, rather than falling through to
+            // a sibling scope — so these groups can't be stacked as separate `web::scope("")`
+            // services. Each route gets its own `web::resource` instead, with the group's
+            // middleware applied per-resource; that keeps the "generate vs. read" rate-limit
+            // bucket and the "open vs. key-gated" auth boundary without the routing conflict.
+            .service(
+                web::resource("/generate")
+                    .wrap(validation::RateLimit::new(generate_limiter.clone(), app_state.config.clone(), generate_rate_limit))
+                    .wrap(auth::ApiKeyAuth::new(app_state.config.clone()))
+                    .route(web::post().to(generate_project)),
+            )
+            .service(
+                web::resource("/generate/stream")
+                    .wrap(validation::RateLimit::new(generate_limiter.clone(), app_state.config.clone(), generate_rate_limit))
+                    .wrap(auth::ApiKeyAuth::new(app_state.config.clone()))
+                    .route(web::post().to(generate_project_stream)),
+            )
+            // Liveness/readiness must stay reachable without a key — they're what an
+            // orchestrator polls before the service (and any key list) is known-good.
+            .service(
+                web::resource("/health")
+                    .wrap(validation::RateLimit::new(read_limiter.clone(), app_state.config.clone(), read_rate_limit))
+                    .route(web::get().to(health)),
+            )
+            .service(
+                web::resource("/health/ready")
+                    .wrap(validation::RateLimit::new(read_limiter.clone(), app_state.config.clone(), read_rate_limit))
+                    .route(web::get().to(health::readiness_check)),
+            )
+            .service(
+                web::resource("/health/live")
+                    .wrap(validation::RateLimit::new(read_limiter.clone(), app_state.config.clone(), read_rate_limit))
+                    .route(web::get().to(health::liveness_check)),
+            )
+            .service(
+                web::resource("/health/detailed")
+                    .wrap(validation::RateLimit::new(read_limiter.clone(), app_state.config.clone(), read_rate_limit))
+                    .wrap(auth::ApiKeyAuth::new(app_state.config.clone()))
+                    .route(web::get().to(health::health_check)),
+            )
+            .service(
+                web::resource("/projects")
+                    .wrap(validation::RateLimit::new(read_limiter.clone(), app_state.config.clone(), read_rate_limit))
+                    .wrap(auth::ApiKeyAuth::new(app_state.config.clone()))
+                    .route(web::get().to(get_projects)),
+            )
+            .service(
+                web::resource("/projects/search")
+                    .wrap(validation::RateLimit::new(read_limiter.clone(), app_state.config.clone(), read_rate_limit))
+                    .wrap(auth::ApiKeyAuth::new(app_state.config.clone()))
+                    .route(web::post().to(search_projects)),
+            )
+            .service(
+                web::resource("/projects/{id}")
+                    .wrap(validation::RateLimit::new(read_limiter.clone(), app_state.config.clone(), read_rate_limit))
+                    .wrap(auth::ApiKeyAuth::new(app_state.config.clone()))
+                    .route(web::get().to(get_project)),
+            )
+            .service(
+                web::resource("/projects/{id}/files/{name}")
+                    .wrap(validation::RateLimit::new(read_limiter.clone(), app_state.config.clone(), read_rate_limit))
+                    .wrap(auth::ApiKeyAuth::new(app_state.config.clone()))
+                    .route(web::get().to(get_project_file)),
+            )
+            .service(
+                web::resource("/ws/projects/{id}")
+                    .wrap(validation::RateLimit::new(read_limiter.clone(), app_state.config.clone(), read_rate_limit))
+                    .wrap(auth::ApiKeyAuth::new(app_state.config.clone()))
+                    .route(web::get().to(project_ws)),
+            )
     })
     .bind("127.0.0.1:8080")?
     .run()
     .await
-} 
\ No newline at end of file
+} 
@@ -0,0 +1,83 @@
+use actix_web::dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::Error;
+use futures_util::future::{ready, LocalBoxFuture, Ready};
+use metrics_exporter_prometheus::{PrometheusBuilder, PrometheusHandle};
+use std::time::Instant;
+
+pub fn install_recorder() -> PrometheusHandle {
+    PrometheusBuilder::new()
+        .install_recorder()
+        .expect("failed to install Prometheus recorder")
+}
+
+/// Actix middleware recording per-route request counts, latency histograms, and an
+/// in-flight gauge. Wrap the `App` with it once; domain counters (generation
+/// duration, AI Core failures, project totals) are recorded separately from the
+/// generation pipeline itself.
+pub struct Metrics;
+
+impl<S, B> Transform<S, ServiceRequest> for Metrics
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = MetricsMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(MetricsMiddleware { service }))
+    }
+}
+
+pub struct MetricsMiddleware<S> {
+    service: S,
+}
+
+impl<S, B> Service<ServiceRequest> for MetricsMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let path = req
+            .match_pattern()
+            .unwrap_or_else(|| req.path().to_string());
+        let method = req.method().to_string();
+        let start = Instant::now();
+
+        metrics::increment_gauge!("genesis_requests_in_flight", 1.0);
+
+        let fut = self.service.call(req);
+
+        Box::pin(async move {
+            let result = fut.await;
+            metrics::decrement_gauge!("genesis_requests_in_flight", 1.0);
+
+            let status = match &result {
+                Ok(res) => res.status().as_u16().to_string(),
+                Err(_) => "500".to_string(),
+            };
+
+            metrics::increment_counter!(
+                "genesis_http_requests_total",
+                "path" => path.clone(), "method" => method.clone(), "status" => status
+            );
+            metrics::histogram!(
+                "genesis_http_request_duration_seconds",
+                start.elapsed().as_secs_f64(),
+                "path" => path, "method" => method
+            );
+
+            result
+        })
+    }
+}
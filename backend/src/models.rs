@@ -6,7 +6,15 @@ use validator::Validate;
 #[derive(Debug, Serialize, Deserialize, Clone, Validate)]
 pub struct GeneratedFile {
     pub name: String,
-    pub content: String,
+    /// Identifier returned by the configured `Store`; fetch bytes via
+    /// `GET /projects/{id}/files/{name}` rather than reading this inline.
+    ///
+    /// `#[serde(default)]` so a project document predating this field (one still
+    /// carrying the old inline `content` string instead) deserializes as an empty
+    /// string here rather than failing the whole project read — run `migrate-store`
+    /// to backfill it before serving that project's files.
+    #[serde(default)]
+    pub storage_key: String,
     pub language: String,
     pub size: Option<u64>,
     pub last_modified: Option<DateTime<Utc>>,
@@ -33,6 +41,14 @@ pub struct ProjectRecord {
     pub updated_at: DateTime<Utc>,
     pub backend: String,
     pub metadata: Option<serde_json::Value>,
+    /// L2-normalized embedding of `prompt`, so cosine similarity reduces to a dot
+    /// product. Absent when the embedding call failed or hasn't run yet.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prompt_embedding: Option<Vec<f32>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub callback_url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub callback_secret: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, Validate)]
@@ -40,6 +56,11 @@ pub struct GenerateRequest {
     #[validate(length(min = 1, message = "Prompt cannot be empty"))]
     pub prompt: String,
     pub backend: Option<String>,
+    /// When set, a signed webhook is POSTed here once the project reaches a
+    /// terminal (`Completed`/`Failed`) status.
+    pub callback_url: Option<String>,
+    /// HMAC-SHA256 key used to sign webhook payloads delivered to `callback_url`.
+    pub callback_secret: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -49,6 +70,18 @@ pub struct ApiResponse<T> {
     pub data: Option<T>,
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SearchRequest {
+    pub query: String,
+    pub top_k: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SearchResult {
+    pub project: ProjectRecord,
+    pub score: f64,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct HealthResponse {
     pub status: String,
@@ -71,6 +104,94 @@ pub struct ProjectUpdate {
     pub files: Option<Vec<GeneratedFile>>,
     pub output: Option<String>,
     pub metadata: Option<serde_json::Value>,
+    pub prompt_embedding: Option<Vec<f32>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub enum JobState {
+    Queued,
+    Running,
+    Retry,
+    Failed,
+    Done,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GenerationJob {
+    pub job_id: String,
+    pub project_id: String,
+    pub prompt: String,
+    pub backend: String,
+    pub state: JobState,
+    pub attempts: u32,
+    pub next_run_at: DateTime<Utc>,
+    pub locked_by: Option<String>,
+    pub locked_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl GenerationJob {
+    pub fn new(job_id: String, project_id: String, prompt: String, backend: String) -> Self {
+        let now = Utc::now();
+
+        Self {
+            job_id,
+            project_id,
+            prompt,
+            backend,
+            state: JobState::Queued,
+            attempts: 0,
+            next_run_at: now,
+            locked_by: None,
+            locked_at: None,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+}
+
+/// An outbound webhook delivery, retried through the same claim/backoff mechanics as
+/// `GenerationJob` since callback endpoints are frequently flaky.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct WebhookJob {
+    pub job_id: String,
+    pub project_id: String,
+    pub url: String,
+    pub secret: Option<String>,
+    /// Raw JSON body to deliver, pre-serialized so retries always sign/send the exact
+    /// same bytes.
+    pub payload: String,
+    pub state: JobState,
+    pub attempts: u32,
+    pub next_run_at: DateTime<Utc>,
+    pub locked_by: Option<String>,
+    pub locked_at: Option<DateTime<Utc>>,
+    pub last_response_status: Option<u16>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl WebhookJob {
+    pub fn new(job_id: String, project_id: String, url: String, secret: Option<String>, payload: String) -> Self {
+        let now = Utc::now();
+
+        Self {
+            job_id,
+            project_id,
+            url,
+            secret,
+            payload,
+            state: JobState::Queued,
+            attempts: 0,
+            next_run_at: now,
+            locked_by: None,
+            locked_at: None,
+            last_response_status: None,
+            created_at: now,
+            updated_at: now,
+        }
+    }
 }
 
 impl ProjectRecord {
@@ -83,11 +204,14 @@ impl ProjectRecord {
             prompt,
             files: Vec::new(),
             output: String::new(),
-            status: ProjectStatus::Generating,
+            status: ProjectStatus::Pending,
             created_at: now,
             updated_at: now,
             backend,
             metadata: None,
+            prompt_embedding: None,
+            callback_url: None,
+            callback_secret: None,
         }
     }
 
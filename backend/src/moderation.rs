@@ -0,0 +1,194 @@
+use regex::RegexSet;
+
+use crate::config::{ModerationAction, ModerationConfig};
+
+/// Result of checking a prompt against a `ModerationPolicy`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ModerationOutcome {
+    Allowed,
+    Warned { category: String },
+    Rejected { category: String },
+}
+
+/// A `ModerationConfig` compiled into a single `RegexSet`, so checking a prompt against
+/// every rule is one O(n) pass instead of one `Regex::find` per rule. Cheap enough to
+/// rebuild per request via `compile`, which keeps it in step with `Config` hot-reloads
+/// (see `config_watch`) without needing its own cache-invalidation plumbing.
+pub struct ModerationPolicy {
+    set: RegexSet,
+    actions: Vec<ModerationAction>,
+    categories: Vec<String>,
+    allowlist: Vec<String>,
+}
+
+impl ModerationPolicy {
+    /// Builds word-boundary, case-insensitive regexes from each rule's pattern so
+    /// "administrator bio" no longer trips a rule written for the whole word "admin".
+    pub fn compile(config: &ModerationConfig) -> anyhow::Result<Self> {
+        let patterns: Vec<String> = config
+            .rules
+            .iter()
+            .map(|rule| format!(r"(?i)\b{}\b", regex::escape(&rule.pattern)))
+            .collect();
+
+        let set = RegexSet::new(&patterns)?;
+        let actions = config.rules.iter().map(|rule| rule.action).collect();
+        let categories = config
+            .rules
+            .iter()
+            .map(|rule| rule.category.clone().unwrap_or_else(|| rule.pattern.clone()))
+            .collect();
+        let allowlist = config.allowlist.iter().map(|phrase| phrase.to_lowercase()).collect();
+
+        Ok(Self { set, actions, categories, allowlist })
+    }
+
+    /// Masks allowlisted phrases out of `text` before matching, so a known-benign
+    /// phrase containing a flagged word can't trigger a rule. Among the rules that
+    /// match, the one listed earliest in `config.rules` decides the outcome — so an
+    /// operator narrows a broad reject pattern by listing a more specific `allow` rule
+    /// ahead of it.
+    pub fn check(&self, text: &str) -> ModerationOutcome {
+        let masked = self.mask_allowlisted(text);
+        let earliest = self.set.matches(&masked).into_iter().min();
+
+        match earliest {
+            Some(index) => match self.actions[index] {
+                ModerationAction::Allow => ModerationOutcome::Allowed,
+                ModerationAction::Reject => ModerationOutcome::Rejected { category: self.categories[index].clone() },
+                ModerationAction::Warn => ModerationOutcome::Warned { category: self.categories[index].clone() },
+            },
+            None => ModerationOutcome::Allowed,
+        }
+    }
+
+    /// Lowercases `text` once and masks allowlisted phrases in that single string —
+    /// matching and replacing on two different strings (e.g. finding a byte offset in
+    /// a lowercased copy but slicing the original) risks landing off a UTF-8 char
+    /// boundary, since `to_lowercase()` can change a string's byte length. The rules
+    /// themselves are already case-insensitive (`(?i)`), so matching against the
+    /// lowercased text doesn't change behavior.
+    fn mask_allowlisted(&self, text: &str) -> String {
+        let mut masked = text.to_lowercase();
+
+        for phrase in &self.allowlist {
+            if phrase.is_empty() {
+                continue;
+            }
+
+            while let Some(pos) = masked.find(phrase.as_str()) {
+                masked.replace_range(pos..pos + phrase.len(), &" ".repeat(phrase.len()));
+            }
+        }
+
+        masked
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ModerationRuleConfig;
+
+    fn reject_rule(pattern: &str) -> ModerationRuleConfig {
+        ModerationRuleConfig {
+            pattern: pattern.to_string(),
+            action: ModerationAction::Reject,
+            category: Some(format!("test:{}", pattern)),
+        }
+    }
+
+    #[test]
+    fn rejects_a_whole_word_match() {
+        let policy = ModerationPolicy::compile(&ModerationConfig {
+            rules: vec![reject_rule("admin")],
+            allowlist: vec![],
+        })
+        .unwrap();
+
+        assert_eq!(
+            policy.check("please grant admin access"),
+            ModerationOutcome::Rejected { category: "test:admin".to_string() }
+        );
+    }
+
+    #[test]
+    fn word_boundary_does_not_match_inside_a_longer_word() {
+        let policy = ModerationPolicy::compile(&ModerationConfig {
+            rules: vec![reject_rule("admin"), reject_rule("system")],
+            allowlist: vec![],
+        })
+        .unwrap();
+
+        assert_eq!(policy.check("write an administrator bio"), ModerationOutcome::Allowed);
+        assert_eq!(policy.check("explain the systematic approach"), ModerationOutcome::Allowed);
+    }
+
+    #[test]
+    fn allowlist_suppresses_a_specific_phrase() {
+        let policy = ModerationPolicy::compile(&ModerationConfig {
+            rules: vec![reject_rule("system")],
+            allowlist: vec!["system requirements".to_string()],
+        })
+        .unwrap();
+
+        assert_eq!(policy.check("list the system requirements"), ModerationOutcome::Allowed);
+        assert_eq!(
+            policy.check("describe the system"),
+            ModerationOutcome::Rejected { category: "test:system".to_string() }
+        );
+    }
+
+    #[test]
+    fn allow_rule_overrides_a_later_reject_rule() {
+        let policy = ModerationPolicy::compile(&ModerationConfig {
+            rules: vec![
+                ModerationRuleConfig {
+                    pattern: "remove duplicates".to_string(),
+                    action: ModerationAction::Allow,
+                    category: None,
+                },
+                reject_rule("remove"),
+            ],
+            allowlist: vec![],
+        })
+        .unwrap();
+
+        assert_eq!(policy.check("remove duplicates from the list"), ModerationOutcome::Allowed);
+        assert_eq!(
+            policy.check("remove the database"),
+            ModerationOutcome::Rejected { category: "test:remove".to_string() }
+        );
+    }
+
+    #[test]
+    fn warn_action_allows_but_is_reported() {
+        let policy = ModerationPolicy::compile(&ModerationConfig {
+            rules: vec![ModerationRuleConfig {
+                pattern: "drop".to_string(),
+                action: ModerationAction::Warn,
+                category: Some("test:drop".to_string()),
+            }],
+            allowlist: vec![],
+        })
+        .unwrap();
+
+        assert_eq!(
+            policy.check("drop the old table"),
+            ModerationOutcome::Warned { category: "test:drop".to_string() }
+        );
+    }
+
+    #[test]
+    fn masking_a_multibyte_allowlist_phrase_does_not_panic() {
+        let policy = ModerationPolicy::compile(&ModerationConfig {
+            rules: vec![reject_rule("admin")],
+            allowlist: vec!["İ admin İ".to_string()],
+        })
+        .unwrap();
+
+        // Must not panic even though `to_lowercase()` can change byte length for
+        // non-ASCII input like the Turkish dotted capital İ.
+        assert_eq!(policy.check("İ admin İ"), ModerationOutcome::Allowed);
+    }
+}
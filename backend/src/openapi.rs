@@ -0,0 +1,24 @@
+use utoipa::OpenApi;
+
+use crate::error::ApiError;
+use crate::health::{HealthStatus, ServiceHealth, SystemInfo};
+use crate::validation::GenerateRequest;
+
+/// Machine-readable contract for the Genesis Backend, served at `/openapi.json` and
+/// browsable via Swagger UI at `/docs`. Keep this in sync as routes are added — utoipa
+/// only picks up what's listed in `paths`/`components` below, it doesn't scan the crate.
+#[derive(OpenApi)]
+#[openapi(
+    paths(
+        crate::generate_project_stream,
+        crate::health::health_check,
+        crate::health::readiness_check,
+        crate::health::liveness_check,
+    ),
+    components(schemas(GenerateRequest, ApiError, HealthStatus, ServiceHealth, SystemInfo)),
+    tags(
+        (name = "generation", description = "Project generation endpoints"),
+        (name = "health", description = "Liveness/readiness/dependency health endpoints"),
+    ),
+)]
+pub struct ApiDoc;
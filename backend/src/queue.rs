@@ -0,0 +1,156 @@
+use actix_web::web;
+use chrono::Utc;
+use log::{error, info, warn};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Semaphore;
+use uuid::Uuid;
+
+use crate::database::DatabaseService;
+use crate::models::{GenerationJob, JobState};
+use crate::{process_generation_job, mark_project_failed, AppState};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+const BASE_BACKOFF: Duration = Duration::from_secs(5);
+const MAX_BACKOFF: Duration = Duration::from_secs(300);
+const LEASE_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// Durable job queue backed by the `jobs` MongoDB collection. Workers claim jobs
+/// atomically via `DatabaseService::claim_job`, so multiple backend instances can
+/// safely share the same queue.
+pub struct JobQueue {
+    db: Arc<DatabaseService>,
+    semaphore: Arc<Semaphore>,
+    max_attempts: u32,
+}
+
+impl JobQueue {
+    pub fn new(db: Arc<DatabaseService>, max_concurrent_generations: usize, max_attempts: u32) -> Self {
+        Self {
+            db,
+            semaphore: Arc::new(Semaphore::new(max_concurrent_generations)),
+            max_attempts,
+        }
+    }
+
+    pub async fn enqueue(&self, project_id: String, prompt: String, backend: String) -> anyhow::Result<String> {
+        let job_id = Uuid::new_v4().to_string();
+        let job = GenerationJob::new(job_id.clone(), project_id, prompt, backend);
+
+        self.db.enqueue_job(job).await?;
+        Ok(job_id)
+    }
+
+    /// Reclaims jobs left `Running` by a worker that crashed before finishing or
+    /// rescheduling them. Call this once at startup before workers begin polling.
+    pub async fn reclaim_crashed_workers(&self) {
+        if let Err(e) = self.db.reclaim_stale_jobs(chrono::Duration::from_std(LEASE_TIMEOUT).unwrap()).await {
+            error!("Failed to sweep stale jobs at startup: {}", e);
+        }
+    }
+
+    pub fn spawn_workers(self: Arc<Self>, app_state: web::Data<AppState>, worker_count: usize) {
+        for i in 0..worker_count {
+            let queue = self.clone();
+            let app_state = app_state.clone();
+            let worker_id = format!("worker-{}", i);
+
+            tokio::spawn(async move {
+                queue.poll_loop(worker_id, app_state).await;
+            });
+        }
+    }
+
+    async fn poll_loop(self: Arc<Self>, worker_id: String, app_state: web::Data<AppState>) {
+        loop {
+            let permit = match self.semaphore.clone().acquire_owned().await {
+                Ok(permit) => permit,
+                Err(_) => return, // semaphore closed, queue is shutting down
+            };
+
+            match self.db.claim_job(&worker_id).await {
+                Ok(Some(job)) => {
+                    let queue = self.clone();
+                    let app_state = app_state.clone();
+
+                    tokio::spawn(async move {
+                        let _permit = permit; // held for the lifetime of the generation
+                        queue.run_claimed_job(job, app_state).await;
+                    });
+                }
+                Ok(None) => {
+                    drop(permit);
+                    tokio::time::sleep(POLL_INTERVAL).await;
+                }
+                Err(e) => {
+                    error!("Worker {} failed to claim job: {}", worker_id, e);
+                    drop(permit);
+                    tokio::time::sleep(POLL_INTERVAL).await;
+                }
+            }
+        }
+    }
+
+    async fn run_claimed_job(&self, job: GenerationJob, app_state: web::Data<AppState>) {
+        info!("Running job {} for project {}", job.job_id, job.project_id);
+
+        match process_generation_job(&job, &app_state).await {
+            Ok(()) => {
+                if let Err(e) = self.db.finish_job(&job.job_id, JobState::Done).await {
+                    error!("Failed to mark job {} done: {}", job.job_id, e);
+                }
+            }
+            Err(message) => self.handle_failure(job, message, app_state).await,
+        }
+    }
+
+    async fn handle_failure(&self, job: GenerationJob, message: String, app_state: web::Data<AppState>) {
+        let attempts = job.attempts + 1;
+
+        if attempts >= self.max_attempts {
+            error!(
+                "Job {} failed permanently after {} attempts: {}",
+                job.job_id, attempts, message
+            );
+            if let Err(e) = self.db.finish_job(&job.job_id, JobState::Failed).await {
+                error!("Failed to mark job {} failed: {}", job.job_id, e);
+            }
+            mark_project_failed(job.project_id, message, app_state).await;
+            return;
+        }
+
+        let backoff = backoff_duration(attempts);
+        warn!(
+            "Job {} failed (attempt {}/{}), retrying in {:?}: {}",
+            job.job_id, attempts, self.max_attempts, backoff, message
+        );
+
+        let next_run_at = Utc::now() + chrono::Duration::from_std(backoff).unwrap();
+        if let Err(e) = self.db.reschedule_job(&job.job_id, next_run_at, attempts).await {
+            error!("Failed to reschedule job {}: {}", job.job_id, e);
+        }
+    }
+}
+
+fn backoff_duration(attempts: u32) -> Duration {
+    let scaled = BASE_BACKOFF.saturating_mul(1u32 << attempts.min(10));
+    scaled.min(MAX_BACKOFF)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_duration_doubles_until_the_cap() {
+        assert_eq!(backoff_duration(0), BASE_BACKOFF);
+        assert_eq!(backoff_duration(1), BASE_BACKOFF * 2);
+        assert_eq!(backoff_duration(2), BASE_BACKOFF * 4);
+    }
+
+    #[test]
+    fn backoff_duration_saturates_at_max_backoff() {
+        assert_eq!(backoff_duration(10), MAX_BACKOFF);
+        assert_eq!(backoff_duration(1000), MAX_BACKOFF);
+    }
+}
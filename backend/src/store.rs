@@ -0,0 +1,159 @@
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures_util::Stream;
+use log::info;
+use std::path::PathBuf;
+use std::pin::Pin;
+use tokio_util::io::ReaderStream;
+
+pub type ByteStream = Pin<Box<dyn Stream<Item = std::io::Result<Bytes>> + Send>>;
+
+/// Inclusive byte range for a partial `load`, as parsed from an HTTP `Range` header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ByteRange {
+    pub start: u64,
+    pub end: u64,
+}
+
+/// Backend-agnostic storage for generated file contents. `GeneratedFile` only carries
+/// the `identifier` this returns from `save`; callers fetch bytes back through `load`.
+#[async_trait]
+pub trait Store: Send + Sync {
+    async fn save(&self, bytes: Bytes, key: &str) -> anyhow::Result<String>;
+    /// Streams `identifier`'s bytes, or just the inclusive `range` of them when given
+    /// (used to serve `Range` requests as `206 Partial Content` in `get_project_file`).
+    async fn load(&self, identifier: &str, range: Option<ByteRange>) -> anyhow::Result<ByteStream>;
+    #[allow(dead_code)]
+    async fn delete(&self, identifier: &str) -> anyhow::Result<()>;
+}
+
+/// Writes files under a configured directory on the local filesystem.
+pub struct FileStore {
+    base_dir: PathBuf,
+}
+
+impl FileStore {
+    pub fn new(base_dir: impl Into<PathBuf>) -> Self {
+        Self { base_dir: base_dir.into() }
+    }
+
+    fn path_for(&self, identifier: &str) -> PathBuf {
+        self.base_dir.join(identifier)
+    }
+}
+
+#[async_trait]
+impl Store for FileStore {
+    async fn save(&self, bytes: Bytes, key: &str) -> anyhow::Result<String> {
+        let path = self.path_for(key);
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+
+        tokio::fs::write(&path, &bytes).await?;
+        info!("Wrote {} bytes to {:?}", bytes.len(), path);
+
+        Ok(key.to_string())
+    }
+
+    async fn load(&self, identifier: &str, range: Option<ByteRange>) -> anyhow::Result<ByteStream> {
+        use tokio::io::{AsyncSeekExt, AsyncReadExt};
+
+        let mut file = tokio::fs::File::open(self.path_for(identifier)).await?;
+
+        let Some(range) = range else {
+            return Ok(Box::pin(ReaderStream::new(file)));
+        };
+
+        file.seek(std::io::SeekFrom::Start(range.start)).await?;
+        let stream = ReaderStream::new(file.take(range.end - range.start + 1));
+        Ok(Box::pin(stream))
+    }
+
+    async fn delete(&self, identifier: &str) -> anyhow::Result<()> {
+        match tokio::fs::remove_file(self.path_for(identifier)).await {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ObjectStoreConfig {
+    pub bucket: String,
+    pub region: String,
+    /// Set for S3-compatible providers (e.g. Backblaze, MinIO); leave unset for AWS.
+    pub endpoint: Option<String>,
+}
+
+impl ObjectStoreConfig {
+    pub fn from_env() -> Option<Self> {
+        let bucket = std::env::var("GENESIS_S3_BUCKET").ok()?;
+        let region = std::env::var("GENESIS_S3_REGION").unwrap_or_else(|_| "us-east-1".to_string());
+        let endpoint = std::env::var("GENESIS_S3_ENDPOINT").ok();
+
+        Some(Self { bucket, region, endpoint })
+    }
+}
+
+/// Talks to an S3-compatible bucket. Credentials are picked up from the standard
+/// `AWS_ACCESS_KEY_ID`/`AWS_SECRET_ACCESS_KEY` environment variables by the SDK.
+pub struct ObjectStore {
+    client: aws_sdk_s3::Client,
+    bucket: String,
+}
+
+impl ObjectStore {
+    pub async fn new(config: ObjectStoreConfig) -> anyhow::Result<Self> {
+        let mut loader = aws_config::defaults(aws_config::BehaviorVersion::latest())
+            .region(aws_sdk_s3::config::Region::new(config.region));
+        if let Some(endpoint) = config.endpoint {
+            loader = loader.endpoint_url(endpoint);
+        }
+        let sdk_config = loader.load().await;
+
+        Ok(Self {
+            client: aws_sdk_s3::Client::new(&sdk_config),
+            bucket: config.bucket,
+        })
+    }
+}
+
+#[async_trait]
+impl Store for ObjectStore {
+    async fn save(&self, bytes: Bytes, key: &str) -> anyhow::Result<String> {
+        self.client
+            .put_object()
+            .bucket(&self.bucket)
+            .key(key)
+            .body(bytes.into())
+            .send()
+            .await?;
+
+        info!("Uploaded object {} to bucket {}", key, self.bucket);
+        Ok(key.to_string())
+    }
+
+    async fn load(&self, identifier: &str, range: Option<ByteRange>) -> anyhow::Result<ByteStream> {
+        let mut request = self.client.get_object().bucket(&self.bucket).key(identifier);
+        if let Some(range) = range {
+            request = request.range(format!("bytes={}-{}", range.start, range.end));
+        }
+
+        let object = request.send().await?;
+
+        Ok(Box::pin(ReaderStream::new(object.body.into_async_read())))
+    }
+
+    async fn delete(&self, identifier: &str) -> anyhow::Result<()> {
+        self.client
+            .delete_object()
+            .bucket(&self.bucket)
+            .key(identifier)
+            .send()
+            .await?;
+
+        Ok(())
+    }
+}
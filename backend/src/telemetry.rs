@@ -0,0 +1,134 @@
+use actix_web::dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform};
+use futures_util::future::{ready, LocalBoxFuture, Ready};
+use std::sync::OnceLock;
+use tracing::Instrument;
+use tracing_subscriber::{reload, EnvFilter, Registry};
+use uuid::Uuid;
+
+use crate::config::LoggingConfig;
+use crate::validation::get_client_id;
+
+tokio::task_local! {
+    static REQUEST_ID: String;
+}
+
+/// Handle onto the live `EnvFilter` layer, set once by `init` and used by
+/// `update_log_level` to re-parse `logging.level` without restarting the process.
+static LOG_FILTER_HANDLE: OnceLock<reload::Handle<EnvFilter, Registry>> = OnceLock::new();
+
+/// Returns the request-id of the request currently executing on this task, if any.
+/// `GenesisError`'s `ResponseError` impl uses this to stamp `ApiError::request_id`
+/// without needing the error to be constructed with the request in hand.
+pub fn current_request_id() -> Option<String> {
+    REQUEST_ID.try_with(|id| id.clone()).ok()
+}
+
+/// Initializes the global `tracing` subscriber from `LoggingConfig`: an `EnvFilter`
+/// parsed from `level`, a JSON or pretty formatter chosen by `format`, and (when built
+/// with `--cfg tokio_unstable`) a `console-subscriber` layer for live `tokio-console`
+/// inspection. Also bridges existing `log::info!`/`error!`/`warn!` call sites into the
+/// same subscriber via `tracing_log`, so nothing else in the crate needs to change.
+///
+/// The `EnvFilter` is wrapped in a `reload::Layer` so `update_log_level` can re-parse
+/// `logging.level` on a `config_watch` reload without restarting the process.
+pub fn init(logging: &LoggingConfig) {
+    use tracing_subscriber::fmt::format::FmtSpan;
+    use tracing_subscriber::layer::SubscriberExt;
+    use tracing_subscriber::util::SubscriberInitExt;
+    use tracing_subscriber::fmt;
+    use tracing_subscriber::Layer;
+
+    let _ = tracing_log::LogTracer::init();
+
+    let filter = EnvFilter::try_new(&logging.level).unwrap_or_else(|_| EnvFilter::new("info"));
+    let (filter, handle) = reload::Layer::new(filter);
+    let _ = LOG_FILTER_HANDLE.set(handle);
+
+    // CLOSE span events let the subscriber log each span's duration (e.g. the
+    // `ai_core_run` span around an upstream call) without us measuring it by hand.
+    let fmt_layer = if logging.format == "json" {
+        fmt::layer().json().with_span_events(FmtSpan::CLOSE).boxed()
+    } else {
+        fmt::layer().pretty().with_span_events(FmtSpan::CLOSE).boxed()
+    };
+
+    let registry = tracing_subscriber::registry().with(filter).with(fmt_layer);
+
+    #[cfg(tokio_unstable)]
+    registry.with(console_subscriber::spawn()).init();
+    #[cfg(not(tokio_unstable))]
+    registry.init();
+}
+
+/// Re-parses `logging.level` into the live `EnvFilter`, called by `config_watch` on
+/// every successful reload so changing the log level takes effect immediately instead
+/// of requiring a restart. A no-op if `init` hasn't run yet.
+pub fn update_log_level(logging: &LoggingConfig) {
+    let Some(handle) = LOG_FILTER_HANDLE.get() else { return };
+
+    match EnvFilter::try_new(&logging.level) {
+        Ok(filter) => {
+            if let Err(e) = handle.reload(filter) {
+                log::error!("Failed to hot-reload log level: {}", e);
+            }
+        }
+        Err(e) => log::error!("Invalid log level '{}' in reloaded config, keeping previous: {}", logging.level, e),
+    }
+}
+
+/// Wraps every request in a span carrying a generated request-id, the originating
+/// client-id, and the service name, so log lines and (if upstream AI Core calls are
+/// nested underneath) their durations can all be correlated back to one request.
+pub struct RequestTelemetry;
+
+impl<S, B> Transform<S, ServiceRequest> for RequestTelemetry
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = actix_web::Error;
+    type Transform = RequestTelemetryMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(RequestTelemetryMiddleware { service }))
+    }
+}
+
+pub struct RequestTelemetryMiddleware<S> {
+    service: S,
+}
+
+impl<S, B> Service<ServiceRequest> for RequestTelemetryMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = actix_web::Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let request_id = Uuid::new_v4().to_string();
+        let client_id = get_client_id(req.request());
+
+        let span = tracing::info_span!(
+            "http_request",
+            service = "genesis-backend",
+            request_id = %request_id,
+            client_id = %client_id,
+            method = %req.method(),
+            path = %req.path(),
+        );
+
+        let fut = self.service.call(req);
+
+        Box::pin(REQUEST_ID.scope(request_id, fut.instrument(span)))
+    }
+}
@@ -1,18 +1,30 @@
-use actix_web::{web, HttpRequest, HttpResponse, Responder};
+use actix_web::body::EitherBody;
+use actix_web::dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::{web, HttpMessage, HttpRequest, HttpResponse};
+use arc_swap::ArcSwap;
+use futures_util::future::{ready, LocalBoxFuture, Ready};
+use log::error;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::sync::Mutex;
-use std::time::{Duration, Instant};
-use validator::{Validate, ValidationError};
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use validator::Validate;
 
+use crate::config::Config;
 use crate::error::GenesisError;
+use crate::moderation::{ModerationOutcome, ModerationPolicy};
 
-#[derive(Debug, Serialize, Deserialize, Validate)]
+const SHARD_COUNT: usize = 16;
+
+#[derive(Debug, Serialize, Deserialize, Validate, utoipa::ToSchema)]
 pub struct GenerateRequest {
     #[validate(length(min = 10, max = 2000, message = "Prompt must be between 10 and 2000 characters"))]
+    #[schema(min_length = 10, max_length = 2000, example = "Build me a React todo app with drag-and-drop")]
     pub prompt: String,
-    
+
     #[validate(regex(path = "BACKEND_REGEX", message = "Backend must be 'ollama' or 'openai'"))]
+    #[schema(example = "ollama")]
     pub backend: Option<String>,
 }
 
@@ -20,9 +32,12 @@ lazy_static::lazy_static! {
     static ref BACKEND_REGEX: regex::Regex = regex::Regex::new(r"^(ollama|openai)$").unwrap();
 }
 
+/// Sliding-window rate limiter keyed by client id. Requests are bucketed into
+/// `SHARD_COUNT` independently-locked shards so clients hashing to different shards
+/// never contend on the same mutex.
 #[derive(Debug)]
 pub struct RateLimiter {
-    requests: Mutex<HashMap<String, Vec<Instant>>>,
+    shards: Vec<Mutex<HashMap<String, Vec<Instant>>>>,
     max_requests: usize,
     window_duration: Duration,
 }
@@ -30,60 +45,270 @@ pub struct RateLimiter {
 impl RateLimiter {
     pub fn new(max_requests: usize, window_duration: Duration) -> Self {
         Self {
-            requests: Mutex::new(HashMap::new()),
+            shards: (0..SHARD_COUNT).map(|_| Mutex::new(HashMap::new())).collect(),
             max_requests,
             window_duration,
         }
     }
-    
-    pub fn is_allowed(&self, client_id: &str) -> bool {
-        let mut requests = self.requests.lock().unwrap();
+
+    fn shard_for(&self, client_id: &str) -> &Mutex<HashMap<String, Vec<Instant>>> {
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        client_id.hash(&mut hasher);
+        &self.shards[(hasher.finish() as usize) % self.shards.len()]
+    }
+
+    /// `override_limit` lets an authenticated API key apply its own quota instead of
+    /// this limiter's global `(max_requests, window_duration)` — the sliding window is
+    /// still tracked per `client_id` either way.
+    pub fn is_allowed(&self, client_id: &str, override_limit: Option<RateLimitOverride>) -> bool {
+        let (max_requests, window_duration) = override_limit
+            .map(|o| (o.max_requests, o.window_duration))
+            .unwrap_or((self.max_requests, self.window_duration));
+
+        let mut shard = self.shard_for(client_id).lock().unwrap();
         let now = Instant::now();
-        
+
         // Clean old requests
-        if let Some(client_requests) = requests.get_mut(client_id) {
-            client_requests.retain(|&time| now.duration_since(time) < self.window_duration);
+        if let Some(client_requests) = shard.get_mut(client_id) {
+            client_requests.retain(|&time| now.duration_since(time) < window_duration);
         }
-        
+
         // Check if limit exceeded
-        let client_requests = requests.entry(client_id.to_string()).or_insert_with(Vec::new);
-        
-        if client_requests.len() >= self.max_requests {
+        let client_requests = shard.entry(client_id.to_string()).or_default();
+
+        if client_requests.len() >= max_requests {
             false
         } else {
             client_requests.push(now);
             true
         }
     }
-    
-    pub fn get_remaining_requests(&self, client_id: &str) -> usize {
-        let requests = self.requests.lock().unwrap();
+
+    pub fn get_remaining_requests(&self, client_id: &str, override_limit: Option<RateLimitOverride>) -> usize {
+        let (max_requests, window_duration) = override_limit
+            .map(|o| (o.max_requests, o.window_duration))
+            .unwrap_or((self.max_requests, self.window_duration));
+
+        let shard = self.shard_for(client_id).lock().unwrap();
         let now = Instant::now();
-        
-        if let Some(client_requests) = requests.get(client_id) {
+
+        if let Some(client_requests) = shard.get(client_id) {
             let valid_requests: Vec<_> = client_requests
                 .iter()
-                .filter(|&&time| now.duration_since(time) < self.window_duration)
+                .filter(|&&time| now.duration_since(time) < window_duration)
                 .collect();
-            
-            self.max_requests.saturating_sub(valid_requests.len())
+
+            max_requests.saturating_sub(valid_requests.len())
         } else {
-            self.max_requests
+            max_requests
+        }
+    }
+
+    /// Drops clients whose requests have all aged out of the window. Run this on a
+    /// timer so long-idle clients don't pin memory forever.
+    pub fn sweep(&self) {
+        let now = Instant::now();
+        for shard in &self.shards {
+            let mut shard = shard.lock().unwrap();
+            shard.retain(|_, times| {
+                times.retain(|&t| now.duration_since(t) < self.window_duration);
+                !times.is_empty()
+            });
+        }
+    }
+}
+
+/// A per-client override of a backend's global `(max_requests, window_duration)`, used
+/// to grant an authenticated API key its own quota (see `auth::AuthenticatedClient`).
+#[derive(Debug, Clone, Copy)]
+pub struct RateLimitOverride {
+    pub max_requests: usize,
+    pub window_duration: Duration,
+}
+
+/// Backend-agnostic rate-limiting contract so `RateLimit` middleware can run against
+/// either the in-process `RateLimiter` or a restart-surviving store like
+/// `SledRateLimiter`, without caring which one it was handed.
+pub trait RateLimiterBackend: Send + Sync {
+    fn is_allowed(&self, client_id: &str, override_limit: Option<RateLimitOverride>) -> bool;
+    fn get_remaining_requests(&self, client_id: &str, override_limit: Option<RateLimitOverride>) -> usize;
+    fn max_requests(&self) -> usize;
+    fn window_duration(&self) -> Duration;
+    /// Drops stale bookkeeping. Called on a timer; implementations for which this is a
+    /// no-op (nothing to sweep) can just do nothing.
+    fn sweep(&self);
+}
+
+impl RateLimiterBackend for RateLimiter {
+    fn is_allowed(&self, client_id: &str, override_limit: Option<RateLimitOverride>) -> bool {
+        RateLimiter::is_allowed(self, client_id, override_limit)
+    }
+
+    fn get_remaining_requests(&self, client_id: &str, override_limit: Option<RateLimitOverride>) -> usize {
+        RateLimiter::get_remaining_requests(self, client_id, override_limit)
+    }
+
+    fn max_requests(&self) -> usize {
+        self.max_requests
+    }
+
+    fn window_duration(&self) -> Duration {
+        self.window_duration
+    }
+
+    fn sweep(&self) {
+        RateLimiter::sweep(self)
+    }
+}
+
+/// Sliding-window rate limiter backed by a `sled` tree, so counters survive process
+/// restarts and (via a shared `sled::Db` on a network filesystem) can be observed by
+/// more than one instance. Each client's window is a JSON-encoded list of
+/// milliseconds-since-epoch timestamps, updated atomically with `Tree::update_and_fetch`
+/// so concurrent requests for the same client can't race each other into a
+/// read-modify-write double count.
+pub struct SledRateLimiter {
+    tree: sled::Tree,
+    max_requests: usize,
+    window_duration: Duration,
+}
+
+impl SledRateLimiter {
+    pub fn new(db: &sled::Db, tree_name: &str, max_requests: usize, window_duration: Duration) -> sled::Result<Self> {
+        Ok(Self {
+            tree: db.open_tree(tree_name)?,
+            max_requests,
+            window_duration,
+        })
+    }
+
+    /// Drops keys whose entire window has aged out, so long-idle clients don't pin
+    /// disk/cache space forever. Run this on the same timer as `RateLimiter::sweep`.
+    fn compact(&self) {
+        let now_millis = now_millis();
+        let window_millis = self.window_duration.as_millis() as u64;
+
+        for key in self.tree.iter().keys().flatten() {
+            let result = self.tree.update_and_fetch(&key, |existing| {
+                let mut timestamps = decode_timestamps(existing);
+                timestamps.retain(|&t| now_millis.saturating_sub(t) < window_millis);
+                (!timestamps.is_empty()).then(|| encode_timestamps(&timestamps))
+            });
+
+            if let Err(e) = result {
+                error!("Sled rate limiter compaction failed for a key: {}", e);
+            }
+        }
+
+        if let Err(e) = self.tree.flush() {
+            error!("Sled rate limiter flush failed: {}", e);
+        }
+    }
+}
+
+impl RateLimiterBackend for SledRateLimiter {
+    fn is_allowed(&self, client_id: &str, override_limit: Option<RateLimitOverride>) -> bool {
+        let (max_requests, window_duration) = override_limit
+            .map(|o| (o.max_requests, o.window_duration))
+            .unwrap_or((self.max_requests, self.window_duration));
+        let now_millis = now_millis();
+        let window_millis = window_duration.as_millis() as u64;
+        let mut allowed = false;
+
+        let result = self.tree.update_and_fetch(client_id.as_bytes(), |existing| {
+            let mut timestamps = decode_timestamps(existing);
+            timestamps.retain(|&t| now_millis.saturating_sub(t) < window_millis);
+
+            allowed = timestamps.len() < max_requests;
+            if allowed {
+                timestamps.push(now_millis);
+            }
+
+            Some(encode_timestamps(&timestamps))
+        });
+
+        if let Err(e) = result {
+            error!("Sled rate limiter update failed for client {}: {} (failing open)", client_id, e);
+            return true;
         }
+
+        allowed
+    }
+
+    fn get_remaining_requests(&self, client_id: &str, override_limit: Option<RateLimitOverride>) -> usize {
+        let (max_requests, window_duration) = override_limit
+            .map(|o| (o.max_requests, o.window_duration))
+            .unwrap_or((self.max_requests, self.window_duration));
+        let now_millis = now_millis();
+        let window_millis = window_duration.as_millis() as u64;
+
+        let valid_requests = match self.tree.get(client_id.as_bytes()) {
+            Ok(existing) => decode_timestamps(existing.as_deref())
+                .into_iter()
+                .filter(|&t| now_millis.saturating_sub(t) < window_millis)
+                .count(),
+            Err(e) => {
+                error!("Sled rate limiter read failed for client {}: {}", client_id, e);
+                0
+            }
+        };
+
+        max_requests.saturating_sub(valid_requests)
+    }
+
+    fn max_requests(&self) -> usize {
+        self.max_requests
+    }
+
+    fn window_duration(&self) -> Duration {
+        self.window_duration
+    }
+
+    fn sweep(&self) {
+        self.compact();
     }
 }
 
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+fn decode_timestamps(bytes: Option<&[u8]>) -> Vec<u64> {
+    bytes.and_then(|b| serde_json::from_slice(b).ok()).unwrap_or_default()
+}
+
+fn encode_timestamps(timestamps: &[u64]) -> Vec<u8> {
+    serde_json::to_vec(timestamps).unwrap_or_default()
+}
+
+/// An authenticated API key's `client_id` (set by `auth::ApiKeyAuth`) takes priority
+/// over the caller's IP, since many clients share an address behind NAT/a proxy. Routes
+/// that skip `ApiKeyAuth` (e.g. liveness) fall back to IP as before.
 pub fn get_client_id(req: &HttpRequest) -> String {
-    // In production, you'd want to use a proper client identification method
-    // For now, we'll use the IP address
+    if let Some(authenticated) = req.extensions().get::<crate::auth::AuthenticatedClient>() {
+        return authenticated.client_id.clone();
+    }
+
     req.connection_info()
         .realip_remote_addr()
         .unwrap_or("unknown")
         .to_string()
 }
 
+/// The `RateLimitOverride` implied by an authenticated client's per-key quota, if any.
+fn override_for(req: &HttpRequest) -> Option<RateLimitOverride> {
+    let authenticated = req.extensions().get::<crate::auth::AuthenticatedClient>()?.clone();
+    let max_requests = authenticated.max_requests?;
+    let window_duration = Duration::from_secs(authenticated.window_seconds.unwrap_or(60));
+    Some(RateLimitOverride { max_requests, window_duration })
+}
+
 pub async fn validate_generate_request(
     req: web::Json<GenerateRequest>,
+    config: &Config,
 ) -> Result<web::Json<GenerateRequest>, GenesisError> {
     // Validate the request
     if let Err(errors) = req.validate() {
@@ -91,53 +316,138 @@ pub async fn validate_generate_request(
             .field_errors()
             .iter()
             .flat_map(|(field, errors)| {
-                errors.iter().map(|error| {
-                    format!("{}: {}", field, error.message.as_ref().unwrap_or(&"Invalid value".to_string()))
+                errors.iter().map(move |error| {
+                    format!("{}: {}", field, error.message.as_deref().unwrap_or("Invalid value"))
                 })
             })
             .collect();
-        
+
         return Err(GenesisError::ValidationError(error_messages.join(", ")));
     }
-    
+
     // Additional business logic validation
     if req.prompt.trim().is_empty() {
         return Err(GenesisError::ValidationError("Prompt cannot be empty".to_string()));
     }
-    
-    // Check for potentially harmful content (basic check)
-    let harmful_keywords = ["delete", "drop", "remove", "system", "admin"];
-    let prompt_lower = req.prompt.to_lowercase();
-    
-    if harmful_keywords.iter().any(|&keyword| prompt_lower.contains(keyword)) {
-        return Err(GenesisError::ValidationError(
-            "Prompt contains potentially harmful keywords".to_string()
-        ));
+
+    // Content moderation: a configurable rule set (word-boundary regex, not a naive
+    // substring check) replaces the old hardcoded keyword list.
+    let policy = ModerationPolicy::compile(&config.moderation)
+        .map_err(|e| GenesisError::InternalError(format!("Failed to compile moderation policy: {}", e)))?;
+
+    match policy.check(&req.prompt) {
+        ModerationOutcome::Rejected { category } => {
+            return Err(GenesisError::ValidationError(format!(
+                "Prompt blocked by content moderation rule: {}",
+                category
+            )));
+        }
+        ModerationOutcome::Warned { category } => {
+            log::warn!("Prompt flagged by moderation rule '{}' but allowed: {:?}", category, req.prompt);
+        }
+        ModerationOutcome::Allowed => {}
     }
-    
+
     Ok(req)
 }
 
-pub fn rate_limit_middleware(
-    rate_limiter: web::Data<RateLimiter>,
-) -> impl Fn(HttpRequest, web::Payload) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<HttpResponse, actix_web::Error>>>> {
-    move |req: HttpRequest, payload: web::Payload| {
-        let rate_limiter = rate_limiter.clone();
-        let client_id = get_client_id(&req);
-        
-        Box::pin(async move {
-            if !rate_limiter.is_allowed(&client_id) {
-                let remaining = rate_limiter.get_remaining_requests(&client_id);
-                return Ok(HttpResponse::TooManyRequests().json(serde_json::json!({
+/// Actix middleware factory wrapping a scope or route in a `RateLimiterBackend`. Mount
+/// one per scope so `/generate` can use a tight bucket while read-only routes use a
+/// looser one; the backend (in-memory vs. `sled`-backed) is the caller's choice.
+///
+/// `quota` reads this scope's `(max_requests, window_duration)` out of the live
+/// `Config` on every request (same as an authenticated key's override), so tuning
+/// `rate_limit.*` in the config file takes effect on the next hot-reload without a
+/// restart — the limiter's own constructor values only seed `sweep`'s retention window.
+pub struct RateLimit {
+    limiter: Arc<dyn RateLimiterBackend>,
+    config: Arc<ArcSwap<Config>>,
+    quota: fn(&Config) -> RateLimitOverride,
+}
+
+impl RateLimit {
+    pub fn new(
+        limiter: Arc<dyn RateLimiterBackend>,
+        config: Arc<ArcSwap<Config>>,
+        quota: fn(&Config) -> RateLimitOverride,
+    ) -> Self {
+        Self { limiter, config, quota }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for RateLimit
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = actix_web::Error;
+    type Transform = RateLimitMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(RateLimitMiddleware {
+            service,
+            limiter: self.limiter.clone(),
+            config: self.config.clone(),
+            quota: self.quota,
+        }))
+    }
+}
+
+pub struct RateLimitMiddleware<S> {
+    service: S,
+    limiter: Arc<dyn RateLimiterBackend>,
+    config: Arc<ArcSwap<Config>>,
+    quota: fn(&Config) -> RateLimitOverride,
+}
+
+impl<S, B> Service<ServiceRequest> for RateLimitMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = actix_web::Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let client_id = get_client_id(req.request());
+        // An authenticated key's own quota wins; otherwise use this scope's live,
+        // hot-reloadable quota from `Config.rate_limit`.
+        let override_limit = Some(override_for(req.request()).unwrap_or_else(|| (self.quota)(&self.config.load_full())));
+
+        if !self.limiter.is_allowed(&client_id, override_limit) {
+            let remaining = self.limiter.get_remaining_requests(&client_id, override_limit);
+            let retry_after = override_limit
+                .map(|o| o.window_duration)
+                .unwrap_or_else(|| self.limiter.window_duration())
+                .as_secs();
+            let max_requests = override_limit
+                .map(|o| o.max_requests)
+                .unwrap_or_else(|| self.limiter.max_requests());
+
+            let response = HttpResponse::TooManyRequests()
+                .insert_header(("X-RateLimit-Limit", max_requests.to_string()))
+                .insert_header(("X-RateLimit-Remaining", remaining.to_string()))
+                .insert_header(("Retry-After", retry_after.to_string()))
+                .json(serde_json::json!({
                     "error": "Rate limit exceeded",
                     "remaining_requests": remaining,
-                    "retry_after": 60
-                })));
-            }
-            
-            // Continue with the request
-            Ok(HttpResponse::Ok().finish())
-        })
+                    "retry_after": retry_after,
+                }));
+
+            let (req, _) = req.into_parts();
+            return Box::pin(async move { Ok(ServiceResponse::new(req, response.map_into_right_body())) });
+        }
+
+        let fut = self.service.call(req);
+        Box::pin(async move { Ok(fut.await?.map_into_left_body()) })
     }
 }
 
@@ -151,14 +461,14 @@ mod tests {
         
         // Should allow first 5 requests
         for i in 0..5 {
-            assert!(limiter.is_allowed("test_client"), "Request {} should be allowed", i);
+            assert!(limiter.is_allowed("test_client", None), "Request {} should be allowed", i);
         }
-        
+
         // Should block the 6th request
-        assert!(!limiter.is_allowed("test_client"), "6th request should be blocked");
-        
+        assert!(!limiter.is_allowed("test_client", None), "6th request should be blocked");
+
         // Should have 0 remaining requests
-        assert_eq!(limiter.get_remaining_requests("test_client"), 0);
+        assert_eq!(limiter.get_remaining_requests("test_client", None), 0);
     }
     
     #[test]
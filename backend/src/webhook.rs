@@ -0,0 +1,156 @@
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use log::{error, info, warn};
+use sha2::Sha256;
+use std::sync::Arc;
+use std::time::Duration;
+use uuid::Uuid;
+
+use crate::database::DatabaseService;
+use crate::models::{JobState, WebhookJob};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+const BASE_BACKOFF: Duration = Duration::from_secs(10);
+const MAX_BACKOFF: Duration = Duration::from_secs(600);
+const MAX_ATTEMPTS: u32 = 5;
+
+/// Delivers outbound project-completion webhooks, retrying flaky endpoints with
+/// exponential backoff through the same `jobs`-style claim mechanics as `JobQueue`.
+pub struct WebhookQueue {
+    db: Arc<DatabaseService>,
+}
+
+impl WebhookQueue {
+    pub fn new(db: Arc<DatabaseService>) -> Self {
+        Self { db }
+    }
+
+    pub async fn enqueue(&self, project_id: String, url: String, secret: Option<String>, payload: String) -> anyhow::Result<String> {
+        let job_id = Uuid::new_v4().to_string();
+        let job = WebhookJob::new(job_id.clone(), project_id, url, secret, payload);
+
+        self.db.enqueue_webhook_job(job).await?;
+        Ok(job_id)
+    }
+
+    pub fn spawn_workers(self: Arc<Self>, worker_count: usize) {
+        for i in 0..worker_count {
+            let queue = self.clone();
+            let worker_id = format!("webhook-worker-{}", i);
+
+            tokio::spawn(async move {
+                queue.poll_loop(worker_id).await;
+            });
+        }
+    }
+
+    async fn poll_loop(self: Arc<Self>, worker_id: String) {
+        loop {
+            match self.db.claim_webhook_job(&worker_id).await {
+                Ok(Some(job)) => self.deliver(job).await,
+                Ok(None) => tokio::time::sleep(POLL_INTERVAL).await,
+                Err(e) => {
+                    error!("Worker {} failed to claim webhook job: {}", worker_id, e);
+                    tokio::time::sleep(POLL_INTERVAL).await;
+                }
+            }
+        }
+    }
+
+    async fn deliver(&self, job: WebhookJob) {
+        let signature = job
+            .secret
+            .as_deref()
+            .map(|secret| sign_payload(secret, job.payload.as_bytes()))
+            .unwrap_or_default();
+
+        let client = reqwest::Client::new();
+        let mut request = client
+            .post(&job.url)
+            .header("Content-Type", "application/json")
+            .timeout(Duration::from_secs(10))
+            .body(job.payload.clone());
+
+        if !signature.is_empty() {
+            request = request.header("X-Genesis-Signature", format!("sha256={}", signature));
+        }
+
+        match request.send().await {
+            Ok(response) if response.status().is_success() => {
+                let status = response.status().as_u16();
+                info!("Delivered webhook {} for project {} ({})", job.job_id, job.project_id, status);
+                if let Err(e) = self.db.finish_webhook_job(&job.job_id, JobState::Done, Some(status)).await {
+                    error!("Failed to mark webhook job {} done: {}", job.job_id, e);
+                }
+            }
+            Ok(response) => self.retry_or_fail(job, Some(response.status().as_u16())).await,
+            Err(e) => {
+                warn!("Webhook delivery {} failed: {}", job.job_id, e);
+                self.retry_or_fail(job, None).await
+            }
+        }
+    }
+
+    async fn retry_or_fail(&self, job: WebhookJob, last_response_status: Option<u16>) {
+        let attempts = job.attempts + 1;
+
+        if attempts >= MAX_ATTEMPTS {
+            error!(
+                "Webhook {} for project {} gave up after {} attempts",
+                job.job_id, job.project_id, attempts
+            );
+            if let Err(e) = self.db.finish_webhook_job(&job.job_id, JobState::Failed, last_response_status).await {
+                error!("Failed to mark webhook job {} failed: {}", job.job_id, e);
+            }
+            return;
+        }
+
+        let backoff = BASE_BACKOFF.saturating_mul(1u32 << attempts.min(6)).min(MAX_BACKOFF);
+        let next_run_at = Utc::now() + chrono::Duration::from_std(backoff).unwrap();
+
+        warn!(
+            "Webhook {} attempt {}/{} failed, retrying in {:?}",
+            job.job_id, attempts, MAX_ATTEMPTS, backoff
+        );
+
+        if let Err(e) = self
+            .db
+            .reschedule_webhook_job(&job.job_id, next_run_at, attempts, last_response_status)
+            .await
+        {
+            error!("Failed to reschedule webhook job {}: {}", job.job_id, e);
+        }
+    }
+}
+
+/// Signs the raw request body with HMAC-SHA256 so receivers can verify authenticity
+/// via the `X-Genesis-Signature: sha256=<hex>` header.
+fn sign_payload(secret: &str, body: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(body);
+    hex::encode(mac.finalize().into_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sign_payload_is_deterministic() {
+        assert_eq!(sign_payload("secret", b"hello"), sign_payload("secret", b"hello"));
+    }
+
+    #[test]
+    fn sign_payload_differs_by_secret_and_body() {
+        let base = sign_payload("secret", b"hello");
+        assert_ne!(base, sign_payload("other-secret", b"hello"));
+        assert_ne!(base, sign_payload("secret", b"goodbye"));
+    }
+
+    #[test]
+    fn sign_payload_matches_known_hmac_sha256_vector() {
+        // Cross-checked against a standalone HMAC-SHA256 computation for this input.
+        let signature = sign_payload("secret", b"hello");
+        assert_eq!(signature, "88aab3ede8d3adf94d26ab90d3bafd4a2083070c3bcce9c014ee04a443847c0b");
+    }
+}
@@ -0,0 +1,139 @@
+use actix::{Actor, ActorFutureExt, AsyncContext, StreamHandler};
+use actix_web_actors::ws as actix_ws;
+use futures_util::StreamExt;
+use log::warn;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tokio::sync::broadcast;
+use tokio_stream::wrappers::BroadcastStream;
+
+use crate::database::DatabaseService;
+use crate::models::{GeneratedFile, ProjectStatus};
+
+const CHANNEL_CAPACITY: usize = 64;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", content = "data")]
+pub enum WsMsg {
+    StatusChanged(ProjectStatus),
+    FileGenerated(GeneratedFile),
+    OutputChunk(String),
+    Completed,
+    Failed(String),
+}
+
+/// Keeps one broadcast channel per project so `ProjectWs` actors can subscribe to the
+/// project they're watching instead of polling the database. Channels are created
+/// lazily and dropped by `publish` once a terminal message is sent or nobody is
+/// subscribed.
+#[derive(Default)]
+pub struct ProjectHub {
+    channels: Mutex<HashMap<String, broadcast::Sender<WsMsg>>>,
+}
+
+impl ProjectHub {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn subscribe(&self, project_id: &str) -> broadcast::Receiver<WsMsg> {
+        let mut channels = self.channels.lock().unwrap();
+        channels
+            .entry(project_id.to_string())
+            .or_insert_with(|| broadcast::channel(CHANNEL_CAPACITY).0)
+            .subscribe()
+    }
+
+    pub fn publish(&self, project_id: &str, msg: WsMsg) {
+        let mut channels = self.channels.lock().unwrap();
+        let is_terminal = matches!(msg, WsMsg::Completed | WsMsg::Failed(_));
+
+        let delivered = {
+            let sender = channels
+                .entry(project_id.to_string())
+                .or_insert_with(|| broadcast::channel(CHANNEL_CAPACITY).0);
+            // Err just means nobody is subscribed right now; late connects fall back to
+            // the persisted project status instead.
+            sender.send(msg).is_ok()
+        };
+
+        // Drop the channel once generation reaches a terminal state (a later
+        // subscriber replays the persisted outcome instead, see `ProjectWs::started`)
+        // or once nobody is listening, so a long-running server doesn't accumulate one
+        // broadcast channel per project forever.
+        if is_terminal || !delivered {
+            channels.remove(project_id);
+        }
+    }
+}
+
+/// A WebSocket session for one client watching one project's generation progress.
+pub struct ProjectWs {
+    project_id: String,
+    db: Arc<DatabaseService>,
+    hub: Arc<ProjectHub>,
+}
+
+impl ProjectWs {
+    pub fn new(project_id: String, db: Arc<DatabaseService>, hub: Arc<ProjectHub>) -> Self {
+        Self { project_id, db, hub }
+    }
+
+    fn send(ctx: &mut actix_ws::WebsocketContext<Self>, msg: &WsMsg) {
+        match serde_json::to_string(msg) {
+            Ok(text) => ctx.text(text),
+            Err(e) => warn!("Failed to serialize WsMsg: {}", e),
+        }
+    }
+}
+
+impl Actor for ProjectWs {
+    type Context = actix_ws::WebsocketContext<Self>;
+
+    fn started(&mut self, ctx: &mut Self::Context) {
+        let receiver = self.hub.subscribe(&self.project_id);
+        ctx.add_stream(BroadcastStream::new(receiver).filter_map(|msg| async move { msg.ok() }));
+
+        // A client that connects after generation already finished should still learn
+        // the terminal outcome, so replay the persisted status once up front.
+        let db = self.db.clone();
+        let project_id = self.project_id.clone();
+        let lookup = actix::fut::wrap_future::<_, Self>(async move { db.get_project(&project_id).await });
+
+        ctx.spawn(lookup.map(|result, _act, ctx| {
+            let project = match result {
+                Ok(Some(project)) => project,
+                Ok(None) => return,
+                Err(e) => {
+                    warn!("Failed to load project status for websocket replay: {}", e);
+                    return;
+                }
+            };
+
+            Self::send(ctx, &WsMsg::StatusChanged(project.status.clone()));
+            match project.status {
+                ProjectStatus::Completed => Self::send(ctx, &WsMsg::Completed),
+                ProjectStatus::Failed => Self::send(ctx, &WsMsg::Failed(project.output.clone())),
+                ProjectStatus::Pending | ProjectStatus::Generating => {}
+            }
+        }));
+    }
+}
+
+impl StreamHandler<WsMsg> for ProjectWs {
+    fn handle(&mut self, msg: WsMsg, ctx: &mut Self::Context) {
+        Self::send(ctx, &msg);
+    }
+}
+
+impl StreamHandler<Result<actix_ws::Message, actix_ws::ProtocolError>> for ProjectWs {
+    fn handle(&mut self, msg: Result<actix_ws::Message, actix_ws::ProtocolError>, ctx: &mut Self::Context) {
+        match msg {
+            Ok(actix_ws::Message::Ping(bytes)) => ctx.pong(&bytes),
+            Ok(actix_ws::Message::Close(reason)) => ctx.close(reason),
+            Ok(_) => {}
+            Err(e) => warn!("WebSocket protocol error: {}", e),
+        }
+    }
+}